@@ -0,0 +1,78 @@
+//! Evaluation time of the programs in `benches/programs`, with their output
+//! discarded. Memoization is left off, since it would turn most of them into a
+//! handful of calls. Baseline numbers are kept in `benches/baseline.md`.
+//!
+//! ```sh
+//! cargo bench --bench eval
+//! ```
+
+use std::{fs, path::Path, thread};
+
+use caramuru::{
+    ast::File,
+    interpreter::{eval, output::Sink, Options},
+    parse_source,
+};
+use criterion::{BatchSize, Criterion};
+
+const PROGRAMS: &[&str] = &[
+    "fib",
+    "sum",
+    "combination",
+    "strings",
+    "list",
+    "walk",
+    "currying",
+];
+
+// Programs recurse once per element or iteration, as deep as `main` allows.
+const STACK_SIZE: usize = 1 << 30;
+
+fn load(name: &str) -> File {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("benches/programs")
+        .join(format!("{name}.rinha"));
+    let source = fs::read_to_string(&path).unwrap();
+
+    parse_source(&format!("{name}.rinha"), &source).unwrap()
+}
+
+fn bench_programs(c: &mut Criterion) {
+    // `sum` recurses deeper than the default limit allows.
+    let options = Options {
+        max_depth: None,
+        ..Options::default()
+    };
+
+    let mut group = c.benchmark_group("eval");
+
+    group.sample_size(10);
+
+    for name in PROGRAMS {
+        let file = load(name);
+
+        group.bench_function(*name, |b| {
+            b.iter_batched(
+                || file.clone(),
+                |file| eval(file, &options, &mut Sink).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn main() {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            let mut criterion = Criterion::default().configure_from_args();
+
+            bench_programs(&mut criterion);
+            criterion.final_summary();
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
@@ -0,0 +1,25 @@
+//! Evaluates random well-typed programs, which must not fail unless they
+//! exceed the limits.
+
+#![no_main]
+
+use caramuru::{
+    ast::generate::Program,
+    interpreter::{eval, output::Buffer, LimitExceeded, Options},
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|program: Program| {
+    let Program(file) = program;
+
+    let options = Options {
+        max_steps: Some(100_000),
+        max_depth: Some(100),
+        max_memory: Some(1 << 20),
+        ..Options::default()
+    };
+
+    if let Err(error) = eval(file, &options, &mut Buffer::default()) {
+        assert!(error.is::<LimitExceeded>(), "{error}");
+    }
+});
@@ -0,0 +1,27 @@
+//! Reads arbitrary JSON as an AST, and evaluates it with and without the
+//! optimizer when it's one.
+
+#![no_main]
+
+use caramuru::{
+    ast::File,
+    interpreter::{eval, output::Buffer, Options},
+    opt::optimize,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|json: &[u8]| {
+    let Ok(file) = serde_json::from_slice::<File>(json) else {
+        return;
+    };
+
+    let options = Options {
+        max_steps: Some(100_000),
+        max_depth: Some(100),
+        max_memory: Some(1 << 20),
+        ..Options::default()
+    };
+
+    let _ = eval(file.clone(), &options, &mut Buffer::default());
+    let _ = eval(optimize(file), &options, &mut Buffer::default());
+});
@@ -0,0 +1,24 @@
+//! Parses arbitrary source code, and evaluates it when it parses.
+
+#![no_main]
+
+use caramuru::{
+    interpreter::{eval, output::Buffer, Options},
+    parse_source,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let Ok(file) = parse_source("fuzz.rinha", source) else {
+        return;
+    };
+
+    let options = Options {
+        max_steps: Some(100_000),
+        max_depth: Some(100),
+        max_memory: Some(1 << 20),
+        ..Options::default()
+    };
+
+    let _ = eval(file, &options, &mut Buffer::default());
+});
@@ -1,34 +1,42 @@
-use serde::Deserialize;
+pub mod build;
+pub mod fold;
+#[cfg(feature = "arbitrary")]
+pub mod generate;
+pub mod shrink;
+pub mod visit;
 
-#[derive(Clone, Debug, Deserialize, Default)]
+use serde::{Deserialize, Serialize};
+
+use crate::parse::SyntaxError;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq, Eq, Hash)]
 pub struct Location {
     pub start: usize,
     pub end: usize,
     pub filename: String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Text {
     pub text: String,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Let {
     pub name: Variable,
     pub value: Box<Term>,
-    pub location: Location,
     pub next: Box<Term>,
+    pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
-#[serde(tag = "Var")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Variable {
     pub text: String,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BinaryOperator {
     Eq,
     Lt,
@@ -45,7 +53,7 @@ pub enum BinaryOperator {
     And,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Binary {
     pub lhs: Box<Term>,
     pub op: BinaryOperator,
@@ -53,7 +61,7 @@ pub struct Binary {
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct If {
     pub condition: Box<Term>,
     pub then: Box<Term>,
@@ -61,64 +69,64 @@ pub struct If {
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Function {
     pub parameters: Vec<Variable>,
     pub value: Box<Term>,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Call {
     pub callee: Box<Term>,
     pub arguments: Vec<Box<Term>>,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Print {
     pub value: Box<Term>,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Tuple {
     pub first: Box<Term>,
     pub second: Box<Term>,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct First {
     pub value: Box<Term>,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Second {
     pub value: Box<Term>,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Boolean {
     pub value: bool,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Str {
     pub value: String,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Integer {
     pub value: i32,
     pub location: Location,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "kind")]
 pub enum Term {
     If(If),
@@ -136,7 +144,27 @@ pub enum Term {
     Function(Function),
 }
 
-#[derive(Debug, Deserialize)]
+impl Term {
+    pub fn location(&self) -> &Location {
+        match self {
+            Term::If(t) => &t.location,
+            Term::Str(t) => &t.location,
+            Term::Let(t) => &t.location,
+            Term::Call(t) => &t.location,
+            Term::First(t) => &t.location,
+            Term::Int(t) => &t.location,
+            Term::Print(t) => &t.location,
+            Term::Tuple(t) => &t.location,
+            Term::Var(t) => &t.location,
+            Term::Bool(t) => &t.location,
+            Term::Second(t) => &t.location,
+            Term::Binary(t) => &t.location,
+            Term::Function(t) => &t.location,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct File {
     pub name: String,
     pub expression: Term,
@@ -182,17 +210,6 @@ impl From<rinha::ast::BinaryOp> for BinaryOperator {
     }
 }
 
-impl From<rinha::ast::Binary> for Binary {
-    fn from(value: rinha::ast::Binary) -> Self {
-        Self {
-            lhs: value.lhs.into(),
-            op: value.op.into(),
-            rhs: value.rhs.into(),
-            location: value.location.into(),
-        }
-    }
-}
-
 impl From<rinha::ast::Str> for Str {
     fn from(value: rinha::ast::Str) -> Self {
         Self {
@@ -211,138 +228,207 @@ impl From<rinha::ast::Bool> for Boolean {
     }
 }
 
-impl From<rinha::ast::Tuple> for Tuple {
-    fn from(value: rinha::ast::Tuple) -> Self {
+impl From<rinha::parser::Var> for Variable {
+    fn from(value: rinha::parser::Var) -> Self {
         Self {
-            first: value.first.into(),
-            second: value.second.into(),
+            text: value.text,
             location: value.location.into(),
         }
     }
 }
 
-impl From<rinha::ast::Call> for Call {
-    fn from(value: rinha::ast::Call) -> Self {
-        Self {
-            arguments: value
-                .arguments
-                .iter()
-                .cloned()
-                .map(Term::from)
-                .map(Box::new)
-                .collect(),
-            callee: value.callee.into(),
-            location: value.location.into(),
+/// The result of converting a term from the reference parser, or every
+/// error node in it.
+type Converted<T> = Result<T, Vec<SyntaxError>>;
+
+fn both<A, B>(a: Converted<A>, b: Converted<B>) -> Converted<(A, B)> {
+    match (a, b) {
+        (Ok(a), Ok(b)) => Ok((a, b)),
+        (a, b) => Err(a.err().into_iter().chain(b.err()).flatten().collect()),
+    }
+}
+
+fn all<T>(items: impl IntoIterator<Item = Converted<T>>) -> Converted<Vec<T>> {
+    let mut converted = Vec::new();
+    let mut errors = Vec::new();
+
+    for item in items {
+        match item {
+            Ok(item) => converted.push(item),
+            Err(item_errors) => errors.extend(item_errors),
         }
     }
+
+    if errors.is_empty() {
+        Ok(converted)
+    } else {
+        Err(errors)
+    }
 }
 
-impl From<rinha::ast::Function> for Function {
-    fn from(value: rinha::ast::Function) -> Self {
-        Self {
-            parameters: value
-                .parameters
-                .iter()
-                .cloned()
-                .map(Variable::from)
-                .collect(),
-            value: value.value.into(),
+fn boxed(term: rinha::ast::Term) -> Converted<Box<Term>> {
+    Term::try_from(term).map(Box::new)
+}
+
+impl TryFrom<rinha::ast::Binary> for Binary {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Binary) -> Converted<Self> {
+        let (lhs, rhs) = both(boxed(*value.lhs), boxed(*value.rhs))?;
+
+        Ok(Self {
+            lhs,
+            op: value.op.into(),
+            rhs,
             location: value.location.into(),
-        }
+        })
     }
 }
 
-impl From<rinha::ast::Let> for Let {
-    fn from(value: rinha::ast::Let) -> Self {
-        Self {
-            name: value.name.into(),
-            value: value.value.into(),
+impl TryFrom<rinha::ast::Tuple> for Tuple {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Tuple) -> Converted<Self> {
+        let (first, second) = both(boxed(*value.first), boxed(*value.second))?;
+
+        Ok(Self {
+            first,
+            second,
             location: value.location.into(),
-            next: value.next.into(),
-        }
+        })
     }
 }
 
-impl From<rinha::ast::If> for If {
-    fn from(value: rinha::ast::If) -> Self {
-        Self {
-            condition: value.condition.into(),
-            then: value.then.into(),
-            otherwise: value.otherwise.into(),
+impl TryFrom<rinha::ast::Call> for Call {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Call) -> Converted<Self> {
+        let arguments = all(value.arguments.into_iter().map(boxed));
+
+        let (callee, arguments) = both(boxed(*value.callee), arguments)?;
+
+        Ok(Self {
+            callee,
+            arguments,
             location: value.location.into(),
-        }
+        })
     }
 }
 
-impl From<rinha::ast::Print> for Print {
-    fn from(value: rinha::ast::Print) -> Self {
-        Self {
-            value: value.value.into(),
+impl TryFrom<rinha::ast::Function> for Function {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Function) -> Converted<Self> {
+        Ok(Self {
+            parameters: value.parameters.into_iter().map(Variable::from).collect(),
+            value: boxed(*value.value)?,
             location: value.location.into(),
-        }
+        })
     }
 }
 
-impl From<rinha::ast::First> for First {
-    fn from(value: rinha::ast::First) -> Self {
-        Self {
-            value: value.value.into(),
+impl TryFrom<rinha::ast::Let> for Let {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Let) -> Converted<Self> {
+        let (bound, next) = both(boxed(*value.value), boxed(*value.next))?;
+
+        Ok(Self {
+            name: value.name.into(),
+            value: bound,
+            next,
             location: value.location.into(),
-        }
+        })
     }
 }
 
-impl From<rinha::ast::Second> for Second {
-    fn from(value: rinha::ast::Second) -> Self {
-        Self {
-            value: value.value.into(),
+impl TryFrom<rinha::ast::If> for If {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::If) -> Converted<Self> {
+        let (condition, (then, otherwise)) = both(
+            boxed(*value.condition),
+            both(boxed(*value.then), boxed(*value.otherwise)),
+        )?;
+
+        Ok(Self {
+            condition,
+            then,
+            otherwise,
             location: value.location.into(),
-        }
+        })
     }
 }
 
-impl From<rinha::parser::Var> for Variable {
-    fn from(value: rinha::parser::Var) -> Self {
-        Self {
-            text: value.text,
+impl TryFrom<rinha::ast::Print> for Print {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Print) -> Converted<Self> {
+        Ok(Self {
+            value: boxed(*value.value)?,
             location: value.location.into(),
-        }
+        })
     }
 }
 
-impl From<rinha::ast::Term> for Term {
-    fn from(value: rinha::ast::Term) -> Self {
-        match value {
-            rinha::ast::Term::Error(..) => unimplemented!(),
+impl TryFrom<rinha::ast::First> for First {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::First) -> Converted<Self> {
+        Ok(Self {
+            value: boxed(*value.value)?,
+            location: value.location.into(),
+        })
+    }
+}
+
+impl TryFrom<rinha::ast::Second> for Second {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Second) -> Converted<Self> {
+        Ok(Self {
+            value: boxed(*value.value)?,
+            location: value.location.into(),
+        })
+    }
+}
+
+impl TryFrom<rinha::ast::Term> for Term {
+    type Error = Vec<SyntaxError>;
+
+    fn try_from(value: rinha::ast::Term) -> Converted<Self> {
+        Ok(match value {
+            rinha::ast::Term::Error(t) => {
+                return Err(vec![SyntaxError::InvalidTerm {
+                    message: t.message,
+                    span: (t.location.start..t.location.end).into(),
+                }])
+            }
             rinha::ast::Term::Int(t) => Term::Int(t.into()),
             rinha::ast::Term::Str(t) => Term::Str(t.into()),
-            rinha::ast::Term::Call(t) => Term::Call(t.into()),
-            rinha::ast::Term::Binary(t) => Term::Binary(t.into()),
-            rinha::ast::Term::Function(t) => Term::Function(t.into()),
-            rinha::ast::Term::Let(t) => Term::Let(t.into()),
-            rinha::ast::Term::If(t) => Term::If(t.into()),
-            rinha::ast::Term::Print(t) => Term::Print(t.into()),
-            rinha::ast::Term::First(t) => Term::First(t.into()),
-            rinha::ast::Term::Second(t) => Term::Second(t.into()),
+            rinha::ast::Term::Call(t) => Term::Call(t.try_into()?),
+            rinha::ast::Term::Binary(t) => Term::Binary(t.try_into()?),
+            rinha::ast::Term::Function(t) => Term::Function(t.try_into()?),
+            rinha::ast::Term::Let(t) => Term::Let(t.try_into()?),
+            rinha::ast::Term::If(t) => Term::If(t.try_into()?),
+            rinha::ast::Term::Print(t) => Term::Print(t.try_into()?),
+            rinha::ast::Term::First(t) => Term::First(t.try_into()?),
+            rinha::ast::Term::Second(t) => Term::Second(t.try_into()?),
             rinha::ast::Term::Bool(t) => Term::Bool(t.into()),
-            rinha::ast::Term::Tuple(t) => Term::Tuple(t.into()),
+            rinha::ast::Term::Tuple(t) => Term::Tuple(t.try_into()?),
             rinha::ast::Term::Var(t) => Term::Var(t.into()),
-        }
+        })
     }
 }
 
-impl From<Box<rinha::ast::Term>> for Box<Term> {
-    fn from(value: Box<rinha::ast::Term>) -> Self {
-        Box::new(Term::from(*value))
-    }
-}
+impl TryFrom<rinha::ast::File> for File {
+    type Error = Vec<SyntaxError>;
 
-impl From<rinha::ast::File> for File {
-    fn from(value: rinha::ast::File) -> Self {
-        Self {
+    fn try_from(value: rinha::ast::File) -> Converted<Self> {
+        Ok(Self {
             name: value.name,
-            expression: value.expression.into(),
+            expression: value.expression.try_into()?,
             location: value.location.into(),
-        }
+        })
     }
 }
@@ -0,0 +1,176 @@
+//! Shorthands for building ASTs from Rust, filling in default [`Location`]s.
+//!
+//! ```
+//! use caramuru::ast::build as b;
+//!
+//! let file = b::file(b::let_("x", b::int(1), b::print(b::add(b::var("x"), b::int(2)))));
+//! ```
+
+use super::{
+    Binary, BinaryOperator, Boolean, Call, File, First, Function, If, Integer, Let, Location,
+    Print, Second, Str, Term, Tuple, Variable,
+};
+
+pub fn file(expression: Term) -> File {
+    File {
+        name: String::new(),
+        expression,
+        location: Location::default(),
+    }
+}
+
+pub fn name(text: impl Into<String>) -> Variable {
+    Variable {
+        text: text.into(),
+        location: Location::default(),
+    }
+}
+
+pub fn int(value: i32) -> Term {
+    Term::Int(Integer {
+        value,
+        location: Location::default(),
+    })
+}
+
+pub fn str(value: impl Into<String>) -> Term {
+    Term::Str(Str {
+        value: value.into(),
+        location: Location::default(),
+    })
+}
+
+pub fn bool(value: bool) -> Term {
+    Term::Bool(Boolean {
+        value,
+        location: Location::default(),
+    })
+}
+
+pub fn var(text: impl Into<String>) -> Term {
+    Term::Var(name(text))
+}
+
+pub fn let_(text: impl Into<String>, value: Term, next: Term) -> Term {
+    Term::Let(Let {
+        name: name(text),
+        value: Box::new(value),
+        next: Box::new(next),
+        location: Location::default(),
+    })
+}
+
+pub fn if_(condition: Term, then: Term, otherwise: Term) -> Term {
+    Term::If(If {
+        condition: Box::new(condition),
+        then: Box::new(then),
+        otherwise: Box::new(otherwise),
+        location: Location::default(),
+    })
+}
+
+pub fn fn_<S: Into<String>>(parameters: impl IntoIterator<Item = S>, value: Term) -> Term {
+    Term::Function(Function {
+        parameters: parameters.into_iter().map(name).collect(),
+        value: Box::new(value),
+        location: Location::default(),
+    })
+}
+
+pub fn call(callee: Term, arguments: impl IntoIterator<Item = Term>) -> Term {
+    Term::Call(Call {
+        callee: Box::new(callee),
+        arguments: arguments.into_iter().map(Box::new).collect(),
+        location: Location::default(),
+    })
+}
+
+pub fn print(value: Term) -> Term {
+    Term::Print(Print {
+        value: Box::new(value),
+        location: Location::default(),
+    })
+}
+
+pub fn tuple(first: Term, second: Term) -> Term {
+    Term::Tuple(Tuple {
+        first: Box::new(first),
+        second: Box::new(second),
+        location: Location::default(),
+    })
+}
+
+pub fn first(value: Term) -> Term {
+    Term::First(First {
+        value: Box::new(value),
+        location: Location::default(),
+    })
+}
+
+pub fn second(value: Term) -> Term {
+    Term::Second(Second {
+        value: Box::new(value),
+        location: Location::default(),
+    })
+}
+
+pub fn binary(lhs: Term, op: BinaryOperator, rhs: Term) -> Term {
+    Term::Binary(Binary {
+        lhs: Box::new(lhs),
+        op,
+        rhs: Box::new(rhs),
+        location: Location::default(),
+    })
+}
+
+pub fn add(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Add, rhs)
+}
+
+pub fn sub(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Sub, rhs)
+}
+
+pub fn mul(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Mul, rhs)
+}
+
+pub fn div(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Div, rhs)
+}
+
+pub fn rem(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Rem, rhs)
+}
+
+pub fn eq(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Eq, rhs)
+}
+
+pub fn neq(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Neq, rhs)
+}
+
+pub fn lt(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Lt, rhs)
+}
+
+pub fn gt(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Gt, rhs)
+}
+
+pub fn lte(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Lte, rhs)
+}
+
+pub fn gte(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Gte, rhs)
+}
+
+pub fn and(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::And, rhs)
+}
+
+pub fn or(lhs: Term, rhs: Term) -> Term {
+    binary(lhs, BinaryOperator::Or, rhs)
+}
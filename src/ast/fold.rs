@@ -0,0 +1,170 @@
+use super::{
+    Binary, Boolean, Call, First, Function, If, Integer, Let, Print, Second, Str, Term, Tuple,
+    Variable,
+};
+
+/// Rewriting of the AST by value.
+///
+/// Every `fold_*` method defaults to the matching `walk_*` function, which
+/// folds the children of the node in evaluation order and rebuilds it. The
+/// methods for compound nodes return a [`Term`], so an implementation can
+/// replace a node with one of a different kind.
+pub trait Fold {
+    fn fold_term(&mut self, term: Term) -> Term {
+        walk_term(self, term)
+    }
+
+    fn fold_let(&mut self, t: Let) -> Term {
+        walk_let(self, t)
+    }
+
+    fn fold_if(&mut self, t: If) -> Term {
+        walk_if(self, t)
+    }
+
+    fn fold_call(&mut self, t: Call) -> Term {
+        walk_call(self, t)
+    }
+
+    fn fold_binary(&mut self, t: Binary) -> Term {
+        walk_binary(self, t)
+    }
+
+    fn fold_function(&mut self, t: Function) -> Term {
+        walk_function(self, t)
+    }
+
+    fn fold_print(&mut self, t: Print) -> Term {
+        walk_print(self, t)
+    }
+
+    fn fold_tuple(&mut self, t: Tuple) -> Term {
+        walk_tuple(self, t)
+    }
+
+    fn fold_first(&mut self, t: First) -> Term {
+        walk_first(self, t)
+    }
+
+    fn fold_second(&mut self, t: Second) -> Term {
+        walk_second(self, t)
+    }
+
+    fn fold_var(&mut self, t: Variable) -> Term {
+        Term::Var(t)
+    }
+
+    fn fold_int(&mut self, t: Integer) -> Term {
+        Term::Int(t)
+    }
+
+    fn fold_str(&mut self, t: Str) -> Term {
+        Term::Str(t)
+    }
+
+    fn fold_bool(&mut self, t: Boolean) -> Term {
+        Term::Bool(t)
+    }
+}
+
+fn fold_box<F: Fold + ?Sized>(folder: &mut F, term: Term) -> Box<Term> {
+    Box::new(folder.fold_term(term))
+}
+
+pub fn walk_term<F: Fold + ?Sized>(folder: &mut F, term: Term) -> Term {
+    match term {
+        Term::If(t) => folder.fold_if(t),
+        Term::Str(t) => folder.fold_str(t),
+        Term::Let(t) => folder.fold_let(t),
+        Term::Call(t) => folder.fold_call(t),
+        Term::First(t) => folder.fold_first(t),
+        Term::Int(t) => folder.fold_int(t),
+        Term::Print(t) => folder.fold_print(t),
+        Term::Tuple(t) => folder.fold_tuple(t),
+        Term::Var(t) => folder.fold_var(t),
+        Term::Bool(t) => folder.fold_bool(t),
+        Term::Second(t) => folder.fold_second(t),
+        Term::Binary(t) => folder.fold_binary(t),
+        Term::Function(t) => folder.fold_function(t),
+    }
+}
+
+pub fn walk_let<F: Fold + ?Sized>(folder: &mut F, t: Let) -> Term {
+    let value = fold_box(folder, *t.value);
+    let next = fold_box(folder, *t.next);
+
+    Term::Let(Let { value, next, ..t })
+}
+
+pub fn walk_if<F: Fold + ?Sized>(folder: &mut F, t: If) -> Term {
+    let condition = fold_box(folder, *t.condition);
+    let then = fold_box(folder, *t.then);
+    let otherwise = fold_box(folder, *t.otherwise);
+
+    Term::If(If {
+        condition,
+        then,
+        otherwise,
+        location: t.location,
+    })
+}
+
+pub fn walk_call<F: Fold + ?Sized>(folder: &mut F, t: Call) -> Term {
+    let callee = fold_box(folder, *t.callee);
+    let arguments = t
+        .arguments
+        .into_iter()
+        .map(|argument| fold_box(folder, *argument))
+        .collect();
+
+    Term::Call(Call {
+        callee,
+        arguments,
+        location: t.location,
+    })
+}
+
+pub fn walk_binary<F: Fold + ?Sized>(folder: &mut F, t: Binary) -> Term {
+    let lhs = fold_box(folder, *t.lhs);
+    let rhs = fold_box(folder, *t.rhs);
+
+    Term::Binary(Binary { lhs, rhs, ..t })
+}
+
+pub fn walk_function<F: Fold + ?Sized>(folder: &mut F, t: Function) -> Term {
+    let value = fold_box(folder, *t.value);
+
+    Term::Function(Function { value, ..t })
+}
+
+pub fn walk_print<F: Fold + ?Sized>(folder: &mut F, t: Print) -> Term {
+    Term::Print(Print {
+        value: fold_box(folder, *t.value),
+        location: t.location,
+    })
+}
+
+pub fn walk_tuple<F: Fold + ?Sized>(folder: &mut F, t: Tuple) -> Term {
+    let first = fold_box(folder, *t.first);
+    let second = fold_box(folder, *t.second);
+
+    Term::Tuple(Tuple {
+        first,
+        second,
+        location: t.location,
+    })
+}
+
+pub fn walk_first<F: Fold + ?Sized>(folder: &mut F, t: First) -> Term {
+    Term::First(First {
+        value: fold_box(folder, *t.value),
+        location: t.location,
+    })
+}
+
+pub fn walk_second<F: Fold + ?Sized>(folder: &mut F, t: Second) -> Term {
+    Term::Second(Second {
+        value: fold_box(folder, *t.value),
+        location: t.location,
+    })
+}
@@ -0,0 +1,374 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::{build as b, File, Term};
+
+const MAX_DEPTH: usize = 5;
+const MAX_TUPLE_DEPTH: usize = 2;
+const MAX_PARAMETERS: usize = 3;
+const MAX_FUEL: i32 = 4;
+const STRINGS: &[&str] = &["", "a", "b", "ab", "rinha"];
+
+#[derive(Clone, PartialEq)]
+enum Type {
+    Int,
+    Bool,
+    Str,
+    Tuple(Box<Type>, Box<Type>),
+}
+
+enum Kind {
+    Value(Type),
+    // Recursive functions take an integer fuel as their first parameter and
+    // stop recursing when it reaches zero.
+    Function {
+        parameters: Vec<Type>,
+        returns: Type,
+        fuel: Option<String>,
+    },
+}
+
+struct Binding {
+    name: String,
+    kind: Kind,
+}
+
+enum Production {
+    Leaf,
+    Let,
+    Function,
+    If,
+    Print,
+    Call(usize),
+    First,
+    Second,
+    Operator,
+}
+
+struct Generator<'a, 'b> {
+    u: &'b mut Unstructured<'a>,
+    scope: Vec<Binding>,
+    names: usize,
+}
+
+impl Generator<'_, '_> {
+    fn name(&mut self, prefix: &str) -> String {
+        self.names += 1;
+
+        format!("{prefix}{}", self.names)
+    }
+
+    fn type_(&mut self, depth: usize) -> Result<Type> {
+        Ok(match self.u.int_in_range(0..=3)? {
+            0 => Type::Int,
+            1 => Type::Bool,
+            2 => Type::Str,
+            _ if depth < MAX_TUPLE_DEPTH => Type::Tuple(
+                Box::new(self.type_(depth + 1)?),
+                Box::new(self.type_(depth + 1)?),
+            ),
+            _ => Type::Int,
+        })
+    }
+
+    fn variables(&self, type_: &Type) -> Vec<String> {
+        self.scope
+            .iter()
+            .filter(|binding| matches!(&binding.kind, Kind::Value(t) if t == type_))
+            .map(|binding| binding.name.clone())
+            .collect()
+    }
+
+    fn leaf(&mut self, type_: &Type) -> Result<Term> {
+        let variables = self.variables(type_);
+
+        if !variables.is_empty() && self.u.arbitrary()? {
+            return Ok(b::var(self.u.choose(&variables)?));
+        }
+
+        Ok(match type_ {
+            Type::Int => b::int(self.u.int_in_range(0..=99)?),
+            Type::Bool => b::bool(self.u.arbitrary()?),
+            Type::Str => b::str(*self.u.choose(STRINGS)?),
+            Type::Tuple(first, second) => b::tuple(self.leaf(first)?, self.leaf(second)?),
+        })
+    }
+
+    fn with_binding<T>(&mut self, binding: Binding, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.scope.push(binding);
+
+        let result = f(self);
+
+        self.scope.pop();
+
+        result
+    }
+
+    fn term(&mut self, type_: &Type, depth: usize) -> Result<Term> {
+        if depth == 0 {
+            return self.leaf(type_);
+        }
+
+        let mut productions = vec![
+            Production::Leaf,
+            Production::Let,
+            Production::Function,
+            Production::If,
+            Production::Print,
+            Production::First,
+            Production::Second,
+            Production::Operator,
+        ];
+
+        productions.extend(
+            self.scope
+                .iter()
+                .enumerate()
+                .filter(|(_, binding)| {
+                    matches!(&binding.kind, Kind::Function { returns, .. } if returns == type_)
+                })
+                .map(|(index, _)| Production::Call(index)),
+        );
+
+        let depth = depth - 1;
+
+        match self.u.choose_iter(productions)? {
+            Production::Leaf => self.leaf(type_),
+            Production::Let => {
+                let name = self.name("x");
+                let value_type = self.type_(0)?;
+                let value = self.term(&value_type, depth)?;
+                let binding = Binding {
+                    name: name.clone(),
+                    kind: Kind::Value(value_type),
+                };
+                let next = self.with_binding(binding, |g| g.term(type_, depth))?;
+
+                Ok(b::let_(name, value, next))
+            }
+            Production::Function => {
+                let (binding, function) = self.function(depth)?;
+                let name = binding.name.clone();
+                let next = self.with_binding(binding, |g| g.term(type_, depth))?;
+
+                Ok(b::let_(name, function, next))
+            }
+            Production::If => Ok(b::if_(
+                self.term(&Type::Bool, depth)?,
+                self.term(type_, depth)?,
+                self.term(type_, depth)?,
+            )),
+            // `print` evaluates to `true`, so its result can only be used
+            // where a boolean is expected.
+            Production::Print => {
+                let value_type = self.type_(0)?;
+                let print = b::print(self.term(&value_type, depth)?);
+
+                if *type_ == Type::Bool {
+                    Ok(print)
+                } else {
+                    Ok(b::let_("_", print, self.term(type_, depth)?))
+                }
+            }
+            Production::First => {
+                let other = self.type_(MAX_TUPLE_DEPTH)?;
+                let tuple = Type::Tuple(Box::new(type_.clone()), Box::new(other));
+
+                Ok(b::first(self.term(&tuple, depth)?))
+            }
+            Production::Second => {
+                let other = self.type_(MAX_TUPLE_DEPTH)?;
+                let tuple = Type::Tuple(Box::new(other), Box::new(type_.clone()));
+
+                Ok(b::second(self.term(&tuple, depth)?))
+            }
+            Production::Call(index) => self.call(index, depth),
+            Production::Operator => self.operator(type_, depth),
+        }
+    }
+
+    fn function(&mut self, depth: usize) -> Result<(Binding, Term)> {
+        let name = self.name("f");
+        let recursive = self.u.arbitrary::<bool>()?;
+        let returns = self.type_(0)?;
+
+        let mut parameters = Vec::new();
+        let mut names = Vec::new();
+
+        for _ in 0..self.u.int_in_range(0..=MAX_PARAMETERS)? {
+            parameters.push(self.type_(0)?);
+            names.push(self.name("p"));
+        }
+
+        let fuel = if recursive {
+            let fuel = self.name("n");
+
+            parameters.insert(0, Type::Int);
+            names.insert(0, fuel.clone());
+
+            Some(fuel)
+        } else {
+            None
+        };
+
+        let scope = self.scope.len();
+
+        for (name, type_) in names.iter().zip(&parameters) {
+            self.scope.push(Binding {
+                name: name.clone(),
+                kind: Kind::Value(type_.clone()),
+            });
+        }
+
+        let body = match &fuel {
+            Some(fuel) => {
+                let base = self.term(&returns, depth / 2)?;
+
+                self.scope.push(Binding {
+                    name: name.clone(),
+                    kind: Kind::Function {
+                        parameters: parameters.clone(),
+                        returns: returns.clone(),
+                        fuel: Some(fuel.clone()),
+                    },
+                });
+
+                let step = self.term(&returns, depth)?;
+
+                b::if_(b::lte(b::var(fuel), b::int(0)), base, step)
+            }
+            None => self.term(&returns, depth)?,
+        };
+
+        self.scope.truncate(scope);
+
+        let binding = Binding {
+            name,
+            kind: Kind::Function {
+                parameters,
+                returns,
+                fuel,
+            },
+        };
+
+        Ok((binding, b::fn_(names, body)))
+    }
+
+    fn call(&mut self, index: usize, depth: usize) -> Result<Term> {
+        let Kind::Function {
+            parameters, fuel, ..
+        } = &self.scope[index].kind
+        else {
+            unreachable!();
+        };
+
+        let name = self.scope[index].name.clone();
+        let parameters = parameters.clone();
+        let fuel = fuel.clone();
+
+        // Inside its own body the fuel parameter is bound, so the call
+        // spends one unit of it. Anywhere else it starts from a small value.
+        let inside = fuel
+            .as_ref()
+            .is_some_and(|fuel| self.scope.iter().any(|binding| &binding.name == fuel));
+
+        let mut arguments = Vec::new();
+
+        for (position, type_) in parameters.iter().enumerate() {
+            arguments.push(match &fuel {
+                Some(fuel) if position == 0 && inside => b::sub(b::var(fuel), b::int(1)),
+                Some(_) if position == 0 => b::int(self.u.int_in_range(0..=MAX_FUEL)?),
+                _ => self.term(type_, depth)?,
+            });
+        }
+
+        Ok(b::call(b::var(name), arguments))
+    }
+
+    // Division and multiplication only take small literals on the right, so
+    // that programs never divide by zero and rarely wrap around.
+    fn operator(&mut self, type_: &Type, depth: usize) -> Result<Term> {
+        Ok(match type_ {
+            Type::Int => {
+                let lhs = self.term(&Type::Int, depth)?;
+
+                match self.u.int_in_range(0..=4)? {
+                    0 => b::add(lhs, self.term(&Type::Int, depth)?),
+                    1 => b::sub(lhs, self.term(&Type::Int, depth)?),
+                    2 => b::mul(lhs, b::int(self.u.int_in_range(0..=9)?)),
+                    3 => b::div(lhs, b::int(self.u.int_in_range(1..=9)?)),
+                    _ => b::rem(lhs, b::int(self.u.int_in_range(1..=9)?)),
+                }
+            }
+            Type::Bool => match self.u.int_in_range(0..=3)? {
+                0 => {
+                    let lhs = self.term(&Type::Int, depth)?;
+                    let rhs = self.term(&Type::Int, depth)?;
+
+                    match self.u.int_in_range(0..=3)? {
+                        0 => b::lt(lhs, rhs),
+                        1 => b::gt(lhs, rhs),
+                        2 => b::lte(lhs, rhs),
+                        _ => b::gte(lhs, rhs),
+                    }
+                }
+                1 => {
+                    let operands = self.type_(0)?;
+                    let lhs = self.term(&operands, depth)?;
+                    let rhs = self.term(&operands, depth)?;
+
+                    if self.u.arbitrary()? {
+                        b::eq(lhs, rhs)
+                    } else {
+                        b::neq(lhs, rhs)
+                    }
+                }
+                2 => b::and(
+                    self.term(&Type::Bool, depth)?,
+                    self.term(&Type::Bool, depth)?,
+                ),
+                _ => b::or(
+                    self.term(&Type::Bool, depth)?,
+                    self.term(&Type::Bool, depth)?,
+                ),
+            },
+            Type::Str => {
+                let (lhs, rhs) = match self.u.int_in_range(0..=2)? {
+                    0 => (Type::Str, Type::Str),
+                    1 => (Type::Str, Type::Int),
+                    _ => (Type::Int, Type::Str),
+                };
+
+                b::add(self.term(&lhs, depth)?, self.term(&rhs, depth)?)
+            }
+            Type::Tuple(first, second) => {
+                b::tuple(self.term(first, depth)?, self.term(second, depth)?)
+            }
+        })
+    }
+}
+
+/// A random well-scoped and well-typed Rinha program.
+///
+/// Every name is bound once, before it is used, and function bodies only read
+/// their parameters and names in scope at every call site, so the result
+/// doesn't depend on dynamic scoping. Functions are only ever called by name.
+/// Recursive functions carry a fuel parameter, so programs always terminate.
+/// Divisors are nonzero literals and arithmetic wraps around, so they
+/// evaluate without errors.
+#[derive(Debug)]
+pub struct Program(pub File);
+
+impl<'a> Arbitrary<'a> for Program {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut generator = Generator {
+            u,
+            scope: Vec::new(),
+            names: 0,
+        };
+
+        let type_ = generator.type_(0)?;
+        let expression = generator.term(&type_, MAX_DEPTH)?;
+
+        Ok(Program(b::file(expression)))
+    }
+}
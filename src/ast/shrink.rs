@@ -0,0 +1,72 @@
+//! Reduces a program that shows some failure to a smaller one that still
+//! shows it, to make failing cases found by random testing readable.
+
+use super::{build as b, Term};
+
+fn children_mut(term: &mut Term) -> Vec<&mut Term> {
+    match term {
+        Term::Let(t) => vec![&mut *t.value, &mut *t.next],
+        Term::If(t) => vec![&mut *t.condition, &mut *t.then, &mut *t.otherwise],
+        Term::Binary(t) => vec![&mut *t.lhs, &mut *t.rhs],
+        Term::Call(t) => t
+            .arguments
+            .iter_mut()
+            .map(|argument| &mut **argument)
+            .collect(),
+        Term::Function(t) => vec![&mut *t.value],
+        Term::Print(t) => vec![&mut *t.value],
+        Term::First(t) => vec![&mut *t.value],
+        Term::Second(t) => vec![&mut *t.value],
+        Term::Tuple(t) => vec![&mut *t.first, &mut *t.second],
+        Term::Int(..) | Term::Str(..) | Term::Bool(..) | Term::Var(..) => Vec::new(),
+    }
+}
+
+/// Every program one step simpler than `term`, the simplest first: `term`
+/// replaced by one of its subterms or by a smaller literal, then the same for
+/// each subterm in place. Candidates may be ill-scoped or ill-typed.
+pub fn candidates(term: &Term) -> Vec<Term> {
+    let mut term = term.clone();
+    let mut simpler = children_mut(&mut term)
+        .into_iter()
+        .map(|child| child.clone())
+        .collect::<Vec<_>>();
+
+    match &term {
+        Term::Int(t) if t.value != 0 => simpler.push(b::int(0)),
+        Term::Str(t) if !t.value.is_empty() => simpler.push(b::str("")),
+        Term::Bool(t) if t.value => simpler.push(b::bool(false)),
+        _ => {}
+    }
+
+    let count = children_mut(&mut term).len();
+
+    for index in 0..count {
+        let child = children_mut(&mut term).swap_remove(index).clone();
+
+        for replacement in candidates(&child) {
+            let mut candidate = term.clone();
+
+            *children_mut(&mut candidate).swap_remove(index) = replacement;
+            simpler.push(candidate);
+        }
+    }
+
+    simpler
+}
+
+/// Shrinks `term` while `fails` holds, by repeatedly taking the first
+/// candidate that still fails, until none does.
+pub fn shrink(mut term: Term, mut fails: impl FnMut(&Term) -> bool) -> Term {
+    'shrinking: loop {
+        for candidate in candidates(&term) {
+            if fails(&candidate) {
+                term = candidate;
+
+                continue 'shrinking;
+            }
+        }
+
+        return term;
+    }
+}
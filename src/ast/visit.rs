@@ -0,0 +1,132 @@
+use super::{
+    Binary, Boolean, Call, First, Function, If, Integer, Let, Print, Second, Str, Term, Tuple,
+    Variable,
+};
+
+/// Read-only traversal of the AST.
+///
+/// Every `visit_*` method defaults to the matching `walk_*` function, which
+/// visits the children of the node in evaluation order. An implementation
+/// overrides the nodes it cares about and calls `walk_*` from them to keep
+/// descending.
+pub trait Visitor<'ast> {
+    fn visit_term(&mut self, term: &'ast Term) {
+        walk_term(self, term)
+    }
+
+    fn visit_let(&mut self, t: &'ast Let) {
+        walk_let(self, t)
+    }
+
+    fn visit_if(&mut self, t: &'ast If) {
+        walk_if(self, t)
+    }
+
+    fn visit_call(&mut self, t: &'ast Call) {
+        walk_call(self, t)
+    }
+
+    fn visit_binary(&mut self, t: &'ast Binary) {
+        walk_binary(self, t)
+    }
+
+    fn visit_function(&mut self, t: &'ast Function) {
+        walk_function(self, t)
+    }
+
+    fn visit_print(&mut self, t: &'ast Print) {
+        walk_print(self, t)
+    }
+
+    fn visit_tuple(&mut self, t: &'ast Tuple) {
+        walk_tuple(self, t)
+    }
+
+    fn visit_first(&mut self, t: &'ast First) {
+        walk_first(self, t)
+    }
+
+    fn visit_second(&mut self, t: &'ast Second) {
+        walk_second(self, t)
+    }
+
+    /// A name being read.
+    fn visit_var(&mut self, _t: &'ast Variable) {}
+
+    /// A name being bound, by a `let` or as a function parameter.
+    fn visit_binding(&mut self, _t: &'ast Variable) {}
+
+    fn visit_int(&mut self, _t: &'ast Integer) {}
+
+    fn visit_str(&mut self, _t: &'ast Str) {}
+
+    fn visit_bool(&mut self, _t: &'ast Boolean) {}
+}
+
+pub fn walk_term<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, term: &'ast Term) {
+    match term {
+        Term::If(t) => visitor.visit_if(t),
+        Term::Str(t) => visitor.visit_str(t),
+        Term::Let(t) => visitor.visit_let(t),
+        Term::Call(t) => visitor.visit_call(t),
+        Term::First(t) => visitor.visit_first(t),
+        Term::Int(t) => visitor.visit_int(t),
+        Term::Print(t) => visitor.visit_print(t),
+        Term::Tuple(t) => visitor.visit_tuple(t),
+        Term::Var(t) => visitor.visit_var(t),
+        Term::Bool(t) => visitor.visit_bool(t),
+        Term::Second(t) => visitor.visit_second(t),
+        Term::Binary(t) => visitor.visit_binary(t),
+        Term::Function(t) => visitor.visit_function(t),
+    }
+}
+
+pub fn walk_let<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast Let) {
+    visitor.visit_binding(&t.name);
+    visitor.visit_term(&t.value);
+    visitor.visit_term(&t.next);
+}
+
+pub fn walk_if<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast If) {
+    visitor.visit_term(&t.condition);
+    visitor.visit_term(&t.then);
+    visitor.visit_term(&t.otherwise);
+}
+
+pub fn walk_call<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast Call) {
+    visitor.visit_term(&t.callee);
+
+    for argument in &t.arguments {
+        visitor.visit_term(argument);
+    }
+}
+
+pub fn walk_binary<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast Binary) {
+    visitor.visit_term(&t.lhs);
+    visitor.visit_term(&t.rhs);
+}
+
+pub fn walk_function<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast Function) {
+    for parameter in &t.parameters {
+        visitor.visit_binding(parameter);
+    }
+
+    visitor.visit_term(&t.value);
+}
+
+pub fn walk_print<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast Print) {
+    visitor.visit_term(&t.value);
+}
+
+pub fn walk_tuple<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast Tuple) {
+    visitor.visit_term(&t.first);
+    visitor.visit_term(&t.second);
+}
+
+pub fn walk_first<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast First) {
+    visitor.visit_term(&t.value);
+}
+
+pub fn walk_second<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, t: &'ast Second) {
+    visitor.visit_term(&t.value);
+}
@@ -0,0 +1,385 @@
+use std::{collections::HashMap, error::Error, fmt::Write, mem};
+
+use crate::{
+    ast::{
+        self,
+        visit::{walk_function, walk_if, walk_let, walk_term, Visitor},
+    },
+    interpreter::{
+        hook::{Call, Hook},
+        Context,
+    },
+    source::Source,
+};
+
+/// A [`Hook`] that counts how many times each term is evaluated and each
+/// function is called.
+#[derive(Default)]
+pub struct Coverage {
+    terms: HashMap<ast::Location, u64>,
+    calls: HashMap<ast::Location, u64>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches the counts with the terms of `file`, which must be the
+    /// evaluated program.
+    pub fn report(&self, file: &ast::File) -> Report {
+        let mut collector = Collector {
+            coverage: self,
+            report: Report::default(),
+            name: None,
+            parent_evaluated: true,
+        };
+
+        collector.visit_term(&file.expression);
+
+        collector.report
+    }
+}
+
+impl Hook for Coverage {
+    fn enter_term(&mut self, term: &ast::Term, _context: &Context) -> Result<(), Box<dyn Error>> {
+        // Only the first evaluation of a term clones its location.
+        match self.terms.get_mut(term.location()) {
+            Some(count) => *count += 1,
+            None => {
+                self.terms.insert(term.location().clone(), 1);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn enter_call(&mut self, call: &Call) -> Result<(), Box<dyn Error>> {
+        if let Some(function) = call.function {
+            match self.calls.get_mut(&function.location) {
+                Some(count) => *count += 1,
+                None => {
+                    self.calls.insert(function.location.clone(), 1);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Counted {
+    pub location: ast::Location,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionCoverage {
+    /// The name the function is bound to, if it's bound directly by a `let`.
+    pub name: Option<String>,
+    pub location: ast::Location,
+    pub calls: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct BranchCoverage {
+    /// The whole `if`, counting how many times it was evaluated.
+    pub condition: Counted,
+    pub then: Counted,
+    pub otherwise: Counted,
+}
+
+/// What a program evaluated, in the order terms appear in it.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub terms: Vec<Counted>,
+    pub lets: Vec<Counted>,
+    pub functions: Vec<FunctionCoverage>,
+    pub branches: Vec<BranchCoverage>,
+    /// The outermost terms that were never evaluated.
+    pub unevaluated: Vec<ast::Location>,
+}
+
+struct Collector<'a> {
+    coverage: &'a Coverage,
+    report: Report,
+    /// The name of the `let` whose value is being visited.
+    name: Option<String>,
+    parent_evaluated: bool,
+}
+
+impl Collector<'_> {
+    fn counted(&self, location: &ast::Location) -> Counted {
+        Counted {
+            location: location.clone(),
+            count: self
+                .coverage
+                .terms
+                .get(location)
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for Collector<'_> {
+    fn visit_term(&mut self, term: &'ast ast::Term) {
+        let counted = self.counted(term.location());
+        let evaluated = counted.count > 0;
+
+        if !evaluated && self.parent_evaluated {
+            self.report.unevaluated.push(counted.location.clone());
+        }
+
+        self.report.terms.push(counted);
+
+        let parent_evaluated = mem::replace(&mut self.parent_evaluated, evaluated);
+
+        walk_term(self, term);
+
+        self.parent_evaluated = parent_evaluated;
+    }
+
+    fn visit_let(&mut self, t: &'ast ast::Let) {
+        self.report.lets.push(self.counted(&t.location));
+        self.name = matches!(*t.value, ast::Term::Function(..)).then(|| t.name.text.clone());
+
+        walk_let(self, t);
+    }
+
+    fn visit_function(&mut self, t: &'ast ast::Function) {
+        self.report.functions.push(FunctionCoverage {
+            name: self.name.take(),
+            location: t.location.clone(),
+            calls: self
+                .coverage
+                .calls
+                .get(&t.location)
+                .copied()
+                .unwrap_or_default(),
+        });
+
+        walk_function(self, t);
+    }
+
+    fn visit_call(&mut self, t: &'ast ast::Call) {
+        // Callees are looked up without being evaluated, so they count as
+        // evaluated with their call.
+        let callee = Counted {
+            location: t.callee.location().clone(),
+            count: self.counted(&t.location).count,
+        };
+
+        self.report.terms.push(callee);
+
+        for argument in &t.arguments {
+            self.visit_term(argument);
+        }
+    }
+
+    fn visit_if(&mut self, t: &'ast ast::If) {
+        let branch = BranchCoverage {
+            condition: self.counted(&t.location),
+            then: self.counted(t.then.location()),
+            otherwise: self.counted(t.otherwise.location()),
+        };
+
+        self.report.branches.push(branch);
+
+        walk_if(self, t);
+    }
+}
+
+impl Report {
+    /// The most times a term starting on each line was evaluated, for lines
+    /// where terms start.
+    fn lines(&self, source: &Source) -> Vec<(usize, u64)> {
+        let mut lines = HashMap::<usize, u64>::new();
+
+        for term in &self.terms {
+            let line = source.position(term.location.start).line;
+            let count = lines.entry(line).or_default();
+
+            *count = (*count).max(term.count);
+        }
+
+        let mut lines = lines.into_iter().collect::<Vec<_>>();
+
+        lines.sort();
+
+        lines
+    }
+
+    fn function_name(&self, function: &FunctionCoverage, source: &Source) -> String {
+        let position = source.position(function.location.start);
+
+        match &function.name {
+            // Functions are named after their line when the name alone is
+            // ambiguous.
+            Some(name)
+                if self
+                    .functions
+                    .iter()
+                    .filter(|other| other.name.as_ref() == Some(name))
+                    .count()
+                    == 1 =>
+            {
+                name.clone()
+            }
+            Some(name) => format!("{name}:{}", position.line),
+            None => format!("<anonymous>:{}:{}", position.line, position.column),
+        }
+    }
+
+    /// The report in the lcov tracefile format, for `path`.
+    pub fn lcov(&self, path: &str, source: &Source) -> String {
+        let mut lcov = String::new();
+
+        writeln!(lcov, "TN:").unwrap();
+        writeln!(lcov, "SF:{path}").unwrap();
+
+        for function in &self.functions {
+            let line = source.position(function.location.start).line;
+
+            writeln!(lcov, "FN:{line},{}", self.function_name(function, source)).unwrap();
+        }
+
+        for function in &self.functions {
+            let name = self.function_name(function, source);
+
+            writeln!(lcov, "FNDA:{},{name}", function.calls).unwrap();
+        }
+
+        let called = self
+            .functions
+            .iter()
+            .filter(|function| function.calls > 0)
+            .count();
+
+        writeln!(lcov, "FNF:{}", self.functions.len()).unwrap();
+        writeln!(lcov, "FNH:{called}").unwrap();
+
+        let mut taken = 0;
+
+        for (block, branch) in self.branches.iter().enumerate() {
+            let line = source.position(branch.condition.location.start).line;
+
+            for (index, counted) in [&branch.then, &branch.otherwise].into_iter().enumerate() {
+                // A branch of an `if` that never ran has no count.
+                let count = if branch.condition.count == 0 {
+                    "-".to_string()
+                } else {
+                    counted.count.to_string()
+                };
+
+                taken += usize::from(counted.count > 0);
+
+                writeln!(lcov, "BRDA:{line},{block},{index},{count}").unwrap();
+            }
+        }
+
+        writeln!(lcov, "BRF:{}", self.branches.len() * 2).unwrap();
+        writeln!(lcov, "BRH:{taken}").unwrap();
+
+        let lines = self.lines(source);
+
+        for (line, count) in &lines {
+            writeln!(lcov, "DA:{line},{count}").unwrap();
+        }
+
+        writeln!(lcov, "LF:{}", lines.len()).unwrap();
+        writeln!(
+            lcov,
+            "LH:{}",
+            lines.iter().filter(|(_, count)| *count > 0).count()
+        )
+        .unwrap();
+        writeln!(lcov, "end_of_record").unwrap();
+
+        lcov
+    }
+
+    /// The source code with how many times each line was evaluated, and the
+    /// parts never evaluated marked with `^`.
+    pub fn listing(&self, source: &Source) -> String {
+        let lines = self.lines(source).into_iter().collect::<HashMap<_, _>>();
+        let width = lines
+            .values()
+            .max()
+            .map_or(1, |count| count.to_string().len());
+
+        let mut listing = String::new();
+
+        for line in 1..=source.line_count() {
+            let Some(range) = source.line_range(line) else {
+                continue;
+            };
+
+            let text = source.line(line).unwrap_or_default();
+            let count = lines.get(&line).map(u64::to_string).unwrap_or_default();
+
+            // The line break at the end of the file doesn't start a line.
+            if line == source.line_count() && text.is_empty() {
+                break;
+            }
+
+            let row = format!("{count:>width$} | {text}");
+
+            writeln!(listing, "{}", row.trim_end()).unwrap();
+
+            let indentation = text.len() - text.trim_start().len();
+            let marks = text
+                .char_indices()
+                .map(|(index, _)| {
+                    let offset = range.start + index;
+                    let unevaluated = self
+                        .unevaluated
+                        .iter()
+                        .any(|location| (location.start..location.end).contains(&offset));
+
+                    if unevaluated && index >= indentation {
+                        '^'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect::<String>();
+
+            if marks.contains('^') {
+                writeln!(listing, "{:width$} | {}", "", marks.trim_end()).unwrap();
+            }
+        }
+
+        let summary = [
+            (
+                "lets",
+                self.lets.iter().filter(|t| t.count > 0).count(),
+                self.lets.len(),
+            ),
+            (
+                "functions",
+                self.functions.iter().filter(|t| t.calls > 0).count(),
+                self.functions.len(),
+            ),
+            (
+                "branches",
+                self.branches
+                    .iter()
+                    .map(|t| usize::from(t.then.count > 0) + usize::from(t.otherwise.count > 0))
+                    .sum(),
+                self.branches.len() * 2,
+            ),
+        ];
+
+        let summary = summary
+            .iter()
+            .map(|(name, covered, total)| format!("{name} {covered}/{total}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        writeln!(listing, "{summary}").unwrap();
+
+        listing
+    }
+}
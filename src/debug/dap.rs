@@ -0,0 +1,548 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    io::{self, BufRead, Write},
+    path::Path,
+    rc::Rc,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use serde_json::{json, Value as Json};
+
+use crate::{
+    interpreter::{Interpreter, Options, Output, Value},
+    load_file,
+    protocol::{read_message, write_message},
+    source::SourceMap,
+};
+
+use super::{Debugger, Frontend, Reason, Session, Step, Stop};
+
+// Rinha programs have a single thread.
+const THREAD_ID: u64 = 1;
+
+/// Returned from the debugger when the client disconnects, to stop evaluation.
+#[derive(Debug)]
+struct Disconnected;
+
+impl fmt::Display for Disconnected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the client disconnected")
+    }
+}
+
+impl Error for Disconnected {}
+
+struct Connection {
+    requests: Receiver<io::Result<Json>>,
+    writer: Box<dyn Write>,
+    seq: u64,
+}
+
+impl Connection {
+    fn read(&mut self) -> io::Result<Option<Json>> {
+        self.requests.recv().ok().transpose()
+    }
+
+    /// The next request, if it already arrived.
+    fn poll(&mut self) -> io::Result<Option<Json>> {
+        self.requests.try_recv().ok().transpose()
+    }
+
+    fn send(&mut self, mut message: Json) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+
+        write_message(self.writer.as_mut(), &message)
+    }
+
+    fn respond(&mut self, request: &Json, body: Json) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }))
+    }
+
+    fn fail(&mut self, request: &Json, message: &str) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }))
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }))
+    }
+
+    /// Answers the requests that don't depend on the state of the program.
+    fn respond_common(&mut self, request: &Json) -> io::Result<()> {
+        match request["command"].as_str().unwrap_or_default() {
+            "threads" => self.respond(
+                request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "setExceptionBreakpoints" => self.respond(request, json!({ "breakpoints": [] })),
+            "pause" => self.respond(request, json!({})),
+            "initialize" | "launch" | "configurationDone" => {
+                self.fail(request, "the program was already launched")
+            }
+            command => self.fail(request, &format!("unsupported request '{command}'")),
+        }
+    }
+}
+
+type Shared = Rc<RefCell<Connection>>;
+
+/// Sends what the program prints as output events.
+struct DapOutput(Shared);
+
+impl Output for DapOutput {
+    fn print(&mut self, line: &str) -> io::Result<()> {
+        self.0.borrow_mut().event(
+            "output",
+            json!({ "category": "stdout", "output": format!("{line}\n") }),
+        )
+    }
+}
+
+fn set_breakpoints(session: &mut Session, arguments: &Json) -> Json {
+    session.clear_breakpoints();
+
+    let breakpoints = arguments["breakpoints"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|breakpoint| {
+            let line = breakpoint["line"].as_u64().unwrap_or_default() as usize;
+            let column = breakpoint["column"].as_u64().map(|column| column as usize);
+
+            match session.add_breakpoint(line, column) {
+                Ok(breakpoint) => json!({
+                    "id": breakpoint.id,
+                    "verified": true,
+                    "line": breakpoint.line,
+                    "column": breakpoint.column,
+                }),
+                Err(message) => json!({
+                    "verified": false,
+                    "line": line,
+                    "message": message,
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    json!({ "breakpoints": breakpoints })
+}
+
+fn variable(name: &str, value: &Value, variables: &mut Vec<Vec<(String, Value)>>) -> Json {
+    // Tuples can be expanded into their elements.
+    let reference = match value {
+        Value::Tuple(tuple) => {
+            variables.push(vec![
+                ("first".to_string(), tuple.0.clone()),
+                ("second".to_string(), tuple.1.clone()),
+            ]);
+
+            variables.len()
+        }
+        _ => 0,
+    };
+
+    json!({
+        "name": name,
+        "value": value.to_string(),
+        "type": value.type_name(),
+        "variablesReference": reference,
+    })
+}
+
+/// A [`Frontend`] answering Debug Adapter Protocol requests while the
+/// program is paused, and the few that make sense while it runs.
+struct DapFrontend {
+    connection: Shared,
+    program: String,
+    stop_on_entry: bool,
+    /// The variables of each `variablesReference` handed out during the
+    /// current pause, starting at 1.
+    variables: Vec<Vec<(String, Value)>>,
+}
+
+impl DapFrontend {
+    fn stack_trace(&self, session: &Session, stop: &Stop) -> Json {
+        let name = Path::new(&self.program)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+
+        let frames = session
+            .stack(stop)
+            .iter()
+            .enumerate()
+            .map(|(id, frame)| {
+                let position = session.position(frame.location);
+
+                json!({
+                    "id": id,
+                    "name": frame.function.map_or("<main>", |function| &function.name),
+                    "source": { "name": name, "path": self.program },
+                    "line": position.map_or(0, |position| position.line),
+                    "column": position.map_or(0, |position| position.column),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "stackFrames": frames, "totalFrames": frames.len() })
+    }
+
+    /// The names visible in a frame: everything bound at the current term
+    /// for the innermost one, only the arguments for the callers.
+    fn bindings(&self, session: &Session, stop: &Stop, frame: usize) -> Vec<(String, Value)> {
+        let mut bindings = if frame == 0 {
+            stop.context
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect::<Vec<_>>()
+        } else {
+            session
+                .stack(stop)
+                .get(frame)
+                .and_then(|frame| frame.function)
+                .map(|function| function.arguments.clone())
+                .unwrap_or_default()
+        };
+
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        bindings
+    }
+
+    fn request(
+        &mut self,
+        session: &mut Session,
+        stop: &Stop,
+        request: &Json,
+    ) -> Result<(), Box<dyn Error>> {
+        let arguments = &request["arguments"];
+        let mut connection = self.connection.borrow_mut();
+
+        match request["command"].as_str().unwrap_or_default() {
+            "setBreakpoints" => {
+                connection.respond(request, set_breakpoints(session, arguments))?;
+            }
+            "stackTrace" => connection.respond(request, self.stack_trace(session, stop))?,
+            "scopes" => {
+                let frame = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+
+                self.variables.push(self.bindings(session, stop, frame));
+
+                let name = if frame == 0 { "Locals" } else { "Arguments" };
+
+                connection.respond(
+                    request,
+                    json!({
+                        "scopes": [{
+                            "name": name,
+                            "variablesReference": self.variables.len(),
+                            "expensive": false,
+                        }],
+                    }),
+                )?;
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or_default();
+                let bindings = (reference as usize)
+                    .checked_sub(1)
+                    .and_then(|index| self.variables.get(index))
+                    .cloned()
+                    .unwrap_or_default();
+
+                let variables = bindings
+                    .iter()
+                    .map(|(name, value)| variable(name, value, &mut self.variables))
+                    .collect::<Vec<_>>();
+
+                connection.respond(request, json!({ "variables": variables }))?;
+            }
+            "evaluate" => {
+                let expression = arguments["expression"].as_str().unwrap_or_default().trim();
+                let frame = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+
+                let value = self
+                    .bindings(session, stop, frame)
+                    .into_iter()
+                    .find(|(name, _)| name == expression);
+
+                match value {
+                    Some((name, value)) => {
+                        let variable = variable(&name, &value, &mut self.variables);
+
+                        connection.respond(
+                            request,
+                            json!({
+                                "result": variable["value"],
+                                "type": variable["type"],
+                                "variablesReference": variable["variablesReference"],
+                            }),
+                        )?;
+                    }
+                    None => connection.fail(request, &format!("'{expression}' does not exist"))?,
+                }
+            }
+            _ => connection.respond_common(request)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl Frontend for DapFrontend {
+    fn pause(&mut self, session: &mut Session, stop: Stop) -> Result<(), Box<dyn Error>> {
+        let (reason, breakpoints) = match stop.reason {
+            Reason::Entry if !self.stop_on_entry => {
+                session.resume(Step::Continue);
+
+                return Ok(());
+            }
+            Reason::Entry => ("entry", vec![]),
+            Reason::Step => ("step", vec![]),
+            Reason::Breakpoint(id) => ("breakpoint", vec![id]),
+            Reason::Pause => ("pause", vec![]),
+        };
+
+        self.variables.clear();
+        self.connection.borrow_mut().event(
+            "stopped",
+            json!({
+                "reason": reason,
+                "threadId": THREAD_ID,
+                "allThreadsStopped": true,
+                "hitBreakpointIds": breakpoints,
+            }),
+        )?;
+
+        loop {
+            let Some(request) = self.connection.borrow_mut().read()? else {
+                return Err(Disconnected.into());
+            };
+
+            let step = match request["command"].as_str().unwrap_or_default() {
+                "continue" => Step::Continue,
+                "next" => Step::Over,
+                "stepIn" => Step::Into,
+                "stepOut" => Step::Out,
+                "disconnect" => {
+                    self.connection.borrow_mut().respond(&request, json!({}))?;
+
+                    return Err(Disconnected.into());
+                }
+                _ => {
+                    self.request(session, &stop, &request)?;
+
+                    continue;
+                }
+            };
+
+            self.connection
+                .borrow_mut()
+                .respond(&request, json!({ "allThreadsContinued": true }))?;
+            session.resume(step);
+
+            return Ok(());
+        }
+    }
+
+    fn poll(&mut self, session: &mut Session) -> Result<(), Box<dyn Error>> {
+        let mut connection = self.connection.borrow_mut();
+
+        while let Some(request) = connection.poll()? {
+            match request["command"].as_str().unwrap_or_default() {
+                "pause" => {
+                    connection.respond(&request, json!({}))?;
+                    session.pause();
+                }
+                "disconnect" => {
+                    connection.respond(&request, json!({}))?;
+
+                    return Err(Disconnected.into());
+                }
+                "setBreakpoints" => {
+                    let body = set_breakpoints(session, &request["arguments"]);
+
+                    connection.respond(&request, body)?;
+                }
+                _ => connection.respond_common(&request)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Launch {
+    program: String,
+    stop_on_entry: bool,
+    file: crate::ast::File,
+    session: Session,
+}
+
+fn launch(arguments: &Json) -> Result<Launch, Box<dyn Error>> {
+    let program = arguments["program"]
+        .as_str()
+        .ok_or("the launch request needs a 'program'")?
+        .to_string();
+
+    let mut sources = SourceMap::new();
+    let file = load_file(&program, &mut sources).map_err(|error| error.to_string())?;
+
+    let session = Session::new(sources.get(&program).cloned());
+
+    Ok(Launch {
+        program,
+        stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or_default(),
+        file,
+        session,
+    })
+}
+
+/// Serves a single debugging session over the Debug Adapter Protocol,
+/// reading requests from `input` and writing responses and events to
+/// `output`, until the client disconnects.
+///
+/// Requests are read on a thread of their own, so that pausing and
+/// disconnecting work while the program runs. It stops at the end of
+/// `input`.
+pub fn serve(
+    mut input: impl BufRead + Send + 'static,
+    output: impl Write + 'static,
+) -> Result<(), Box<dyn Error>> {
+    let (sender, requests) = mpsc::channel();
+
+    thread::spawn(move || {
+        while let Some(request) = read_message(&mut input).transpose() {
+            let failed = request.is_err();
+
+            if sender.send(request).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let connection = Rc::new(RefCell::new(Connection {
+        requests,
+        writer: Box::new(output),
+        seq: 0,
+    }));
+
+    let mut launched = None;
+
+    // Configure the session until the client is done with it.
+    loop {
+        let mut connection = connection.borrow_mut();
+
+        let Some(request) = connection.read()? else {
+            return Ok(());
+        };
+
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => connection.respond(
+                &request,
+                json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsEvaluateForHovers": true,
+                }),
+            )?,
+            "launch" => match launch(&request["arguments"]) {
+                Ok(launch) => {
+                    launched = Some(launch);
+                    connection.respond(&request, json!({}))?;
+                    connection.event("initialized", json!({}))?;
+                }
+                Err(error) => connection.fail(&request, &error.to_string())?,
+            },
+            "setBreakpoints" => match launched.as_mut() {
+                Some(launch) => {
+                    let body = set_breakpoints(&mut launch.session, &request["arguments"]);
+
+                    connection.respond(&request, body)?;
+                }
+                None => connection.fail(&request, "no program was launched")?,
+            },
+            "configurationDone" if launched.is_some() => {
+                connection.respond(&request, json!({}))?;
+
+                break;
+            }
+            "configurationDone" => connection.fail(&request, "no program was launched")?,
+            "disconnect" => return Ok(connection.respond(&request, json!({}))?),
+            _ => connection.respond_common(&request)?,
+        }
+    }
+
+    let Some(Launch {
+        program,
+        stop_on_entry,
+        file,
+        session,
+    }) = launched
+    else {
+        unreachable!("configuration only finishes after a launch");
+    };
+
+    let mut interpreter = Interpreter::new(Options::default(), DapOutput(connection.clone()));
+
+    interpreter.set_hook(Debugger::new(
+        session,
+        DapFrontend {
+            connection: connection.clone(),
+            program,
+            stop_on_entry,
+            variables: Vec::new(),
+        },
+    ));
+
+    let result = interpreter.eval(&file);
+
+    drop(interpreter);
+
+    let mut connection = connection.borrow_mut();
+
+    let exit_code = match result {
+        Ok(_) => 0,
+        Err(error) if error.is::<Disconnected>() => return Ok(()),
+        Err(error) => {
+            connection.event(
+                "output",
+                json!({ "category": "stderr", "output": format!("{error}\n") }),
+            )?;
+
+            1
+        }
+    };
+
+    connection.event("exited", json!({ "exitCode": exit_code }))?;
+    connection.event("terminated", json!({}))?;
+
+    while let Some(request) = connection.read()? {
+        match request["command"].as_str().unwrap_or_default() {
+            "disconnect" => return Ok(connection.respond(&request, json!({}))?),
+            "stackTrace" => {
+                connection.respond(&request, json!({ "stackFrames": [], "totalFrames": 0 }))?
+            }
+            _ => connection.respond_common(&request)?,
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,323 @@
+use std::error::Error;
+
+use crate::{
+    ast,
+    interpreter::{
+        hook::{Call, Hook},
+        Context, Value,
+    },
+    source::{Position, Source},
+};
+
+mod dap;
+mod prompt;
+
+pub use self::{dap::serve, prompt::Prompt};
+
+#[derive(Clone, Debug)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub line: usize,
+    /// Without a column, the breakpoint stops at the first term evaluated on
+    /// its line each time evaluation reaches it.
+    pub column: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Continue,
+    Into,
+    Over,
+    Out,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    Entry,
+    Step,
+    Breakpoint(usize),
+    /// The frontend asked to pause with [`Session::pause`].
+    Pause,
+}
+
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub name: String,
+    pub arguments: Vec<(String, Value)>,
+    /// Where the function was called.
+    pub location: ast::Location,
+}
+
+/// Where evaluation paused, handed to a [`Frontend`].
+pub struct Stop<'a> {
+    pub term: &'a ast::Term,
+    pub context: &'a Context,
+    pub reason: Reason,
+}
+
+/// A frame of the call stack at a [`Stop`]. The outermost one is the program
+/// itself, which has no function.
+pub struct StackFrame<'a> {
+    pub function: Option<&'a Frame>,
+    /// Where evaluation is in this frame.
+    pub location: &'a ast::Location,
+}
+
+/// The state of a debugging session: breakpoints, how to resume and the
+/// calls in progress.
+pub struct Session {
+    source: Option<Source>,
+    breakpoints: Vec<Breakpoint>,
+    next_breakpoint: usize,
+    step: Step,
+    step_depth: usize,
+    frames: Vec<Frame>,
+    last: Option<(usize, usize)>,
+    started: bool,
+    pausing: bool,
+}
+
+impl Session {
+    /// Starts a session that stops before the first term. Without `source`
+    /// positions are only known as byte offsets, so there are no
+    /// breakpoints.
+    pub fn new(source: Option<Source>) -> Self {
+        Self {
+            source,
+            breakpoints: Vec::new(),
+            next_breakpoint: 1,
+            step: Step::Into,
+            step_depth: 0,
+            frames: Vec::new(),
+            last: None,
+            started: false,
+            pausing: false,
+        }
+    }
+
+    pub fn source(&self) -> Option<&Source> {
+        self.source.as_ref()
+    }
+
+    pub fn position(&self, location: &ast::Location) -> Option<Position> {
+        Some(self.source.as_ref()?.position(location.start))
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(
+        &mut self,
+        line: usize,
+        column: Option<usize>,
+    ) -> Result<&Breakpoint, String> {
+        let source = self
+            .source
+            .as_ref()
+            .ok_or("breakpoints need the source code")?;
+
+        if source.line(line).is_none() {
+            return Err(format!("there is no line {line}"));
+        }
+
+        if let Some(column) = column {
+            source
+                .offset(Position { line, column })
+                .ok_or_else(|| format!("there is no column {column} on line {line}"))?;
+        }
+
+        self.breakpoints.push(Breakpoint {
+            id: self.next_breakpoint,
+            line,
+            column,
+        });
+        self.next_breakpoint += 1;
+
+        Ok(&self.breakpoints[self.breakpoints.len() - 1])
+    }
+
+    pub fn remove_breakpoint(&mut self, id: usize) -> bool {
+        let count = self.breakpoints.len();
+
+        self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+
+        self.breakpoints.len() != count
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// The calls in progress, outermost first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// The call stack at `stop`, innermost first.
+    pub fn stack<'a>(&'a self, stop: &Stop<'a>) -> Vec<StackFrame<'a>> {
+        let mut location = stop.term.location();
+        let mut stack = Vec::new();
+
+        for frame in self.frames.iter().rev() {
+            stack.push(StackFrame {
+                function: Some(frame),
+                location,
+            });
+
+            location = &frame.location;
+        }
+
+        stack.push(StackFrame {
+            function: None,
+            location,
+        });
+
+        stack
+    }
+
+    pub fn resume(&mut self, step: Step) {
+        self.step = step;
+        self.step_depth = self.frames.len();
+        self.pausing = false;
+    }
+
+    /// Pauses at the next term that stepping would stop at, whatever the
+    /// session was resumed with.
+    pub fn pause(&mut self) {
+        self.pausing = true;
+    }
+
+    fn should_pause(&mut self, term: &ast::Term) -> Option<Reason> {
+        let depth = self.frames.len();
+        let position = self.position(term.location());
+
+        // A line breakpoint stops once per visit to its line: when the line
+        // changes or a call starts over on the same line.
+        let new_line = position.is_some_and(|position| self.last != Some((position.line, depth)));
+
+        self.last = position.map(|position| (position.line, depth));
+
+        if let Some(position) = position {
+            let breakpoint = self
+                .breakpoints
+                .iter()
+                .find(|breakpoint| match breakpoint.column {
+                    Some(column) => (position.line, position.column) == (breakpoint.line, column),
+                    None => position.line == breakpoint.line && new_line,
+                });
+
+            if let Some(breakpoint) = breakpoint {
+                return Some(Reason::Breakpoint(breakpoint.id));
+            }
+        }
+
+        // Stepping skips literals and names, which do nothing interesting.
+        if matches!(
+            term,
+            ast::Term::Int(..)
+                | ast::Term::Str(..)
+                | ast::Term::Bool(..)
+                | ast::Term::Var(..)
+                | ast::Term::Function(..)
+        ) {
+            return None;
+        }
+
+        if self.pausing {
+            self.started = true;
+
+            return Some(Reason::Pause);
+        }
+
+        let pause = match self.step {
+            Step::Continue => false,
+            Step::Into => true,
+            Step::Over => depth <= self.step_depth,
+            Step::Out => depth < self.step_depth,
+        };
+
+        match (pause, self.started) {
+            (false, _) => None,
+            (true, false) => {
+                self.started = true;
+
+                Some(Reason::Entry)
+            }
+            (true, true) => Some(Reason::Step),
+        }
+    }
+}
+
+/// The user interface of a debugger, asked what to do whenever evaluation
+/// pauses. It must call [`Session::resume`] before returning, and can stop
+/// evaluation by returning an error.
+pub trait Frontend {
+    fn pause(&mut self, session: &mut Session, stop: Stop) -> Result<(), Box<dyn Error>>;
+
+    /// Called before every term while the program runs, to handle what the
+    /// user asks for in the meantime, like [`Session::pause`].
+    fn poll(&mut self, _session: &mut Session) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// A [`Hook`] that pauses evaluation at breakpoints and steps, handing
+/// control to a [`Frontend`].
+pub struct Debugger<F> {
+    session: Session,
+    frontend: F,
+}
+
+impl<F: Frontend> Debugger<F> {
+    pub fn new(session: Session, frontend: F) -> Self {
+        Self { session, frontend }
+    }
+}
+
+impl<F: Frontend> Hook for Debugger<F> {
+    fn enter_term(&mut self, term: &ast::Term, context: &Context) -> Result<(), Box<dyn Error>> {
+        self.frontend.poll(&mut self.session)?;
+
+        match self.session.should_pause(term) {
+            Some(reason) => self.frontend.pause(
+                &mut self.session,
+                Stop {
+                    term,
+                    context,
+                    reason,
+                },
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn enter_call(&mut self, call: &Call) -> Result<(), Box<dyn Error>> {
+        let parameters = call
+            .function
+            .map(|function| function.parameters.as_slice())
+            .unwrap_or_default();
+
+        self.session.frames.push(Frame {
+            name: call.name.to_string(),
+            arguments: parameters
+                .iter()
+                .zip(call.arguments)
+                .map(|(parameter, argument)| (parameter.text.clone(), argument.clone()))
+                .collect(),
+            location: call.location.clone(),
+        });
+
+        Ok(())
+    }
+
+    fn exit_call(
+        &mut self,
+        _call: &Call,
+        _result: &Result<Value, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.session.frames.pop();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    error::Error,
+    io::{BufRead, Write},
+};
+
+use crate::{ast, source::describe_location};
+
+use super::{Frontend, Reason, Session, Step, Stop};
+
+const HELP: &str = "\
+Commands:
+  s, step               step into the next term, entering calls
+  n, next               step to the next term, over calls
+  o, out                run until the current call returns
+  c, continue           run until the next breakpoint
+  b, break LINE[:COL]   stop at a line, or at the term starting at a column
+  b, break              list breakpoints
+  d, delete [ID]        remove a breakpoint, or all of them
+  l, locals             show the names bound at this point
+  p, print NAME         show the value of a name
+  bt, backtrace         show the calls in progress
+  w, where              show the current position
+  q, quit               stop evaluation
+An empty line repeats the previous command.";
+
+/// A command line interface for the debugger, reading commands from `input`
+/// and writing to `output`.
+pub struct Prompt<R, W> {
+    input: R,
+    output: W,
+    last_command: String,
+}
+
+impl<R: BufRead, W: Write> Prompt<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            last_command: String::new(),
+        }
+    }
+
+    fn location(&self, session: &Session, location: &ast::Location) -> String {
+        describe_location(location, session.position(location))
+    }
+
+    fn show_position(
+        &mut self,
+        session: &Session,
+        location: &ast::Location,
+    ) -> Result<(), Box<dyn Error>> {
+        let (Some(source), Some(position)) = (session.source(), session.position(location)) else {
+            return Ok(());
+        };
+
+        let line = source.line(position.line).unwrap_or_default();
+        let gutter = position.line.to_string().len();
+
+        writeln!(self.output, "{} | {}", position.line, line)?;
+        writeln!(
+            self.output,
+            "{:gutter$} | {:indent$}^",
+            "",
+            "",
+            indent = position.column - 1
+        )?;
+
+        Ok(())
+    }
+
+    fn backtrace(&mut self, session: &Session, stop: &Stop) -> Result<(), Box<dyn Error>> {
+        for (index, frame) in session.stack(stop).iter().enumerate() {
+            let location = self.location(session, frame.location);
+
+            match frame.function {
+                Some(function) => {
+                    let arguments = function
+                        .arguments
+                        .iter()
+                        .map(|(name, value)| format!("{name} = {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    writeln!(
+                        self.output,
+                        "#{index} {}({arguments}) at {location}",
+                        function.name
+                    )?;
+                }
+                None => writeln!(self.output, "#{index} <main> at {location}")?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn breakpoint(&mut self, session: &mut Session, argument: &str) -> Result<(), Box<dyn Error>> {
+        if argument.is_empty() {
+            for breakpoint in session.breakpoints() {
+                match breakpoint.column {
+                    Some(column) => writeln!(
+                        self.output,
+                        "{}: line {} column {column}",
+                        breakpoint.id, breakpoint.line
+                    )?,
+                    None => writeln!(self.output, "{}: line {}", breakpoint.id, breakpoint.line)?,
+                }
+            }
+
+            return Ok(());
+        }
+
+        let (line, column) = match argument.split_once(':') {
+            Some((line, column)) => (line, Some(column)),
+            None => (argument, None),
+        };
+
+        let line = line.parse::<usize>();
+        let column = column.map(str::parse::<usize>).transpose();
+
+        let message = match (line, column) {
+            (Ok(line), Ok(column)) => match session.add_breakpoint(line, column) {
+                Ok(breakpoint) => format!("breakpoint {} at line {line}", breakpoint.id),
+                Err(error) => error,
+            },
+            _ => format!("invalid position '{argument}'"),
+        };
+
+        writeln!(self.output, "{message}")?;
+
+        Ok(())
+    }
+
+    fn delete(&mut self, session: &mut Session, argument: &str) -> Result<(), Box<dyn Error>> {
+        if argument.is_empty() {
+            session.clear_breakpoints();
+        } else if !argument
+            .parse()
+            .is_ok_and(|id| session.remove_breakpoint(id))
+        {
+            writeln!(self.output, "no breakpoint {argument}")?;
+        }
+
+        Ok(())
+    }
+
+    fn locals(&mut self, stop: &Stop) -> Result<(), Box<dyn Error>> {
+        let mut bindings = stop.context.iter().collect::<Vec<_>>();
+
+        bindings.sort_by_key(|(name, _)| *name);
+
+        for (name, value) in bindings {
+            writeln!(self.output, "{name} = {value}")?;
+        }
+
+        Ok(())
+    }
+
+    fn command(
+        &mut self,
+        session: &mut Session,
+        stop: &Stop,
+        command: &str,
+        argument: &str,
+    ) -> Result<Option<Step>, Box<dyn Error>> {
+        let location = stop.term.location();
+
+        match command {
+            "s" | "step" => return Ok(Some(Step::Into)),
+            "n" | "next" => return Ok(Some(Step::Over)),
+            "o" | "out" => return Ok(Some(Step::Out)),
+            "c" | "continue" => return Ok(Some(Step::Continue)),
+            "q" | "quit" => return Err("stopped by the debugger".into()),
+            "b" | "break" => self.breakpoint(session, argument)?,
+            "d" | "delete" => self.delete(session, argument)?,
+            "l" | "locals" => self.locals(stop)?,
+            "p" | "print" => match stop.context.get(argument) {
+                Some(value) => writeln!(self.output, "{argument} = {value}")?,
+                None => writeln!(self.output, "'{argument}' does not exist")?,
+            },
+            "bt" | "backtrace" => self.backtrace(session, stop)?,
+            "w" | "where" => {
+                writeln!(self.output, "{}", self.location(session, location))?;
+                self.show_position(session, location)?;
+            }
+            "h" | "help" => writeln!(self.output, "{HELP}")?,
+            "" => {}
+            command => writeln!(self.output, "unknown command '{command}', try 'help'")?,
+        }
+
+        Ok(None)
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for Prompt<R, W> {
+    fn pause(&mut self, session: &mut Session, stop: Stop) -> Result<(), Box<dyn Error>> {
+        let location = stop.term.location();
+
+        match stop.reason {
+            Reason::Entry => writeln!(
+                self.output,
+                "stopped at {}",
+                self.location(session, location)
+            )?,
+            Reason::Step | Reason::Pause => {
+                writeln!(self.output, "{}", self.location(session, location))?
+            }
+            Reason::Breakpoint(id) => writeln!(
+                self.output,
+                "breakpoint {id} at {}",
+                self.location(session, location)
+            )?,
+        }
+
+        self.show_position(session, location)?;
+
+        loop {
+            write!(self.output, "(rinha) ")?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+
+            if self.input.read_line(&mut line)? == 0 {
+                return Err("stopped by the debugger".into());
+            }
+
+            let line = match line.trim() {
+                "" => self.last_command.clone(),
+                line => line.to_string(),
+            };
+
+            self.last_command = line.clone();
+
+            let (command, argument) = line.split_once(' ').unwrap_or((&line, ""));
+
+            if let Some(step) = self.command(session, &stop, command, argument.trim())? {
+                session.resume(step);
+
+                return Ok(());
+            }
+        }
+    }
+}
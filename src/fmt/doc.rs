@@ -0,0 +1,109 @@
+const INDENT: usize = 2;
+
+// A layout document in the style of Wadler's "prettier printer": groups are
+// printed on a single line when they fit the width, and broken at each of
+// their `Line`s otherwise.
+#[derive(Clone, Debug)]
+pub enum Doc {
+    Text(String),
+    // A space when the enclosing group is flat, a newline otherwise.
+    Line,
+    // Nothing when the enclosing group is flat, a newline otherwise.
+    SoftLine,
+    // Always a newline, which breaks every enclosing group.
+    HardLine,
+    Concat(Vec<Doc>),
+    Nest(Box<Doc>),
+    Group(Box<Doc>),
+}
+
+pub fn text(text: impl Into<String>) -> Doc {
+    Doc::Text(text.into())
+}
+
+pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Doc {
+    Doc::Concat(docs.into_iter().collect())
+}
+
+pub fn nest(doc: Doc) -> Doc {
+    Doc::Nest(Box::new(doc))
+}
+
+pub fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+type Command<'a> = (usize, Mode, &'a Doc);
+
+fn fits(width: isize, doc: &Doc, rest: &[Command]) -> bool {
+    let mut remaining = width;
+    let mut commands = vec![(Mode::Flat, doc)];
+    let mut rest = rest.iter().rev();
+
+    while remaining >= 0 {
+        let Some((mode, doc)) = commands
+            .pop()
+            .or_else(|| rest.next().map(|(_, mode, doc)| (*mode, *doc)))
+        else {
+            return true;
+        };
+
+        match doc {
+            Doc::Text(text) => remaining -= text.chars().count() as isize,
+            Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+            Doc::Line => remaining -= 1,
+            Doc::SoftLine => {}
+            Doc::HardLine => return mode == Mode::Break,
+            Doc::Concat(docs) => commands.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            Doc::Nest(doc) | Doc::Group(doc) => commands.push((mode, doc)),
+        }
+    }
+
+    false
+}
+
+pub fn render(doc: &Doc, width: usize) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    let mut commands: Vec<Command> = vec![(0, Mode::Break, doc)];
+
+    while let Some((indent, mode, doc)) = commands.pop() {
+        match doc {
+            Doc::Text(text) => {
+                output.push_str(text);
+                column += text.chars().count();
+            }
+            Doc::Line if mode == Mode::Flat => {
+                output.push(' ');
+                column += 1;
+            }
+            Doc::SoftLine if mode == Mode::Flat => {}
+            Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                output.push('\n');
+                output.push_str(&" ".repeat(indent));
+                column = indent;
+            }
+            Doc::Concat(docs) => commands.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            Doc::Nest(doc) => commands.push((indent + INDENT, mode, doc)),
+            Doc::Group(doc) => {
+                let mode = if mode == Mode::Flat
+                    || fits(width as isize - column as isize, doc, &commands)
+                {
+                    Mode::Flat
+                } else {
+                    Mode::Break
+                };
+
+                commands.push((indent, mode, doc));
+            }
+        }
+    }
+
+    output
+}
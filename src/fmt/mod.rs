@@ -0,0 +1,253 @@
+use crate::ast;
+
+mod doc;
+
+use self::doc::{concat, group, nest, render, text, Doc};
+
+pub const DEFAULT_WIDTH: usize = 80;
+
+// How tightly a term binds, from the grammar of the reference parser. An
+// operand that binds less tightly than its position requires is wrapped in
+// parentheses.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Apply,
+    Factor,
+    Arithmetic,
+    Logical,
+    Term,
+}
+
+fn operator_level(op: &ast::BinaryOperator) -> Level {
+    match op {
+        ast::BinaryOperator::Mul | ast::BinaryOperator::Div | ast::BinaryOperator::Rem => {
+            Level::Factor
+        }
+        ast::BinaryOperator::Add | ast::BinaryOperator::Sub => Level::Arithmetic,
+        _ => Level::Logical,
+    }
+}
+
+fn operator_symbol(op: &ast::BinaryOperator) -> &'static str {
+    match op {
+        ast::BinaryOperator::Add => "+",
+        ast::BinaryOperator::Sub => "-",
+        ast::BinaryOperator::Mul => "*",
+        ast::BinaryOperator::Div => "/",
+        ast::BinaryOperator::Rem => "%",
+        ast::BinaryOperator::Eq => "==",
+        ast::BinaryOperator::Neq => "!=",
+        ast::BinaryOperator::Lt => "<",
+        ast::BinaryOperator::Gt => ">",
+        ast::BinaryOperator::Lte => "<=",
+        ast::BinaryOperator::Gte => ">=",
+        ast::BinaryOperator::And => "&&",
+        ast::BinaryOperator::Or => "||",
+    }
+}
+
+fn level(term: &ast::Term) -> Level {
+    match term {
+        ast::Term::Binary(t) => operator_level(&t.op),
+        ast::Term::Let(..) | ast::Term::If(..) | ast::Term::Function(..) | ast::Term::Tuple(..) => {
+            Level::Term
+        }
+        _ => Level::Apply,
+    }
+}
+
+// Rinha has no negative literals, so they can only come from a JSON AST or
+// from constant folding. They are written as a subtraction from zero.
+fn integer(value: i32) -> Doc {
+    match value {
+        i32::MIN => text(format!("((0 - {}) - 1)", i32::MAX)),
+        value if value < 0 => text(format!("(0 - {})", -value)),
+        value => text(value.to_string()),
+    }
+}
+
+fn parenthesized(doc: Doc) -> Doc {
+    group(concat([
+        text("("),
+        nest(concat([Doc::SoftLine, doc])),
+        Doc::SoftLine,
+        text(")"),
+    ]))
+}
+
+fn arguments<'a>(terms: impl IntoIterator<Item = &'a ast::Term>) -> Doc {
+    let mut docs = Vec::new();
+
+    for (index, term) in terms.into_iter().enumerate() {
+        if index > 0 {
+            docs.push(text(","));
+            docs.push(Doc::Line);
+        }
+
+        docs.push(expression(term));
+    }
+
+    parenthesized(concat(docs))
+}
+
+fn block(term: &ast::Term) -> Doc {
+    group(concat([
+        text("{"),
+        nest(concat([Doc::Line, sequence(term)])),
+        Doc::Line,
+        text("}"),
+    ]))
+}
+
+fn operand(term: &ast::Term, required: Level) -> Doc {
+    if level(term) <= required {
+        expression(term)
+    } else {
+        parenthesized(expression(term))
+    }
+}
+
+fn conditional(t: &ast::If, line: Doc) -> Doc {
+    concat([
+        text("if "),
+        parenthesized(expression(&t.condition)),
+        text(" {"),
+        nest(concat([line.clone(), sequence(&t.then)])),
+        line.clone(),
+        text("} else {"),
+        nest(concat([line.clone(), sequence(&t.otherwise)])),
+        line,
+        text("}"),
+    ])
+}
+
+// A term where a chain of `let`s reads as a sequence of statements: the whole
+// file, the rest of a `let`, the bodies of blocks and the branches of
+// conditionals.
+fn sequence(term: &ast::Term) -> Doc {
+    match term {
+        ast::Term::Let(t) => {
+            // Only operators are moved to the next line when they don't fit,
+            // everything else breaks inside its own parentheses or braces.
+            let value = match t.value.as_ref() {
+                ast::Term::Binary(..) => group(nest(concat([Doc::Line, expression(&t.value)]))),
+                value => concat([text(" "), expression(value)]),
+            };
+
+            concat([
+                text(format!("let {} =", t.name.text)),
+                value,
+                text(";"),
+                Doc::HardLine,
+                sequence(&t.next),
+            ])
+        }
+        // Conditionals used as statements are always spread over lines.
+        ast::Term::If(t) => conditional(t, Doc::HardLine),
+        term => expression(term),
+    }
+}
+
+fn expression(term: &ast::Term) -> Doc {
+    match term {
+        ast::Term::Let(..) => block(term),
+        ast::Term::Int(t) => integer(t.value),
+        ast::Term::Str(t) => text(format!("\"{}\"", t.value)),
+        ast::Term::Bool(t) => text(t.value.to_string()),
+        ast::Term::Var(t) => text(t.text.clone()),
+        ast::Term::Print(t) => concat([text("print"), arguments([t.value.as_ref()])]),
+        ast::Term::First(t) => concat([text("first"), arguments([t.value.as_ref()])]),
+        ast::Term::Second(t) => concat([text("second"), arguments([t.value.as_ref()])]),
+        ast::Term::Tuple(t) => arguments([t.first.as_ref(), t.second.as_ref()]),
+        ast::Term::Call(t) => concat([
+            operand(&t.callee, Level::Apply),
+            arguments(t.arguments.iter().map(|argument| argument.as_ref())),
+        ]),
+        ast::Term::Function(t) => concat([
+            text("fn "),
+            arguments(
+                &t.parameters
+                    .iter()
+                    .map(|parameter| ast::Term::Var(parameter.clone()))
+                    .collect::<Vec<_>>(),
+            ),
+            text(" => "),
+            block(&t.value),
+        ]),
+        ast::Term::If(t) => group(conditional(t, Doc::Line)),
+        ast::Term::Binary(t) => {
+            let level = operator_level(&t.op);
+
+            // Operators are right associative: the left operand must bind
+            // more tightly than the operator, the right one at least as
+            // tightly. A chain like `a + b - c` is laid out as one group.
+            let lhs_level = match level {
+                Level::Factor => Level::Apply,
+                Level::Arithmetic => Level::Factor,
+                _ => Level::Arithmetic,
+            };
+
+            let mut docs = vec![operand(&t.lhs, lhs_level)];
+            let mut rest = t;
+
+            loop {
+                docs.push(text(format!(" {}", operator_symbol(&rest.op))));
+                docs.push(Doc::Line);
+
+                match rest.rhs.as_ref() {
+                    ast::Term::Binary(rhs) if operator_level(&rhs.op) == level => {
+                        docs.push(operand(&rhs.lhs, lhs_level));
+                        rest = rhs;
+                    }
+                    rhs => {
+                        docs.push(operand(rhs, level));
+                        break;
+                    }
+                }
+            }
+
+            let first = docs.remove(0);
+
+            group(concat([first, nest(concat(docs))]))
+        }
+    }
+}
+
+/// Formats `file` as Rinha source code, fitting lines in `width` columns
+/// where possible.
+///
+/// Comments are not part of the AST, so they can't be preserved: use
+/// [`has_comments`] to find out whether formatting a source would drop any.
+/// Strings are written back exactly as they appear in the AST.
+pub fn format_file(file: &ast::File, width: usize) -> String {
+    let mut output = render(&sequence(&file.expression), width);
+
+    output.push('\n');
+
+    output
+}
+
+/// Whether `source` has any `//` or `/* */` comment outside of strings.
+pub fn has_comments(source: &str) -> bool {
+    let mut characters = source.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' => {
+                while let Some(character) = characters.next() {
+                    match character {
+                        '\\' => {
+                            characters.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if matches!(characters.peek(), Some('/' | '*')) => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
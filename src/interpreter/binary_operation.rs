@@ -2,14 +2,43 @@ use std::error::Error;
 
 use crate::ast;
 
-use super::{eval_term, value::Value, Context};
+use super::{eval_term, heap::Heap, value::Value, Context, State};
 
-fn binary_operation_sum(lhs_value: &Value, rhs_value: &Value) -> Result<Value, Box<dyn Error>> {
+// The result is accounted for before it is built, so a single concatenation
+// can't go over the memory limit.
+fn concatenate(
+    heap: &Heap,
+    location: &ast::Location,
+    lhs: &str,
+    rhs: &str,
+) -> Result<Value, Box<dyn Error>> {
+    heap.reserve(lhs.len() + rhs.len(), location)?;
+
+    let mut value = String::with_capacity(lhs.len() + rhs.len());
+
+    value.push_str(lhs);
+    value.push_str(rhs);
+
+    let bytes = value.capacity();
+
+    Ok(Value::String(heap.allocate(value, bytes)))
+}
+
+fn binary_operation_sum(
+    heap: &Heap,
+    location: &ast::Location,
+    lhs_value: &Value,
+    rhs_value: &Value,
+) -> Result<Value, Box<dyn Error>> {
     match (lhs_value, rhs_value) {
-        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs + rhs)),
-        (Value::Integer(lhs), Value::String(rhs)) => Ok(Value::String(format!("{}{}", lhs, rhs))),
-        (Value::String(lhs), Value::Integer(rhs)) => Ok(Value::String(format!("{}{}", lhs, rhs))),
-        (Value::String(lhs), Value::String(rhs)) => Ok(Value::String(format!("{}{}", lhs, rhs))),
+        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs.wrapping_add(*rhs))),
+        (Value::Integer(lhs), Value::String(rhs)) => {
+            concatenate(heap, location, &lhs.to_string(), rhs)
+        }
+        (Value::String(lhs), Value::Integer(rhs)) => {
+            concatenate(heap, location, lhs, &rhs.to_string())
+        }
+        (Value::String(lhs), Value::String(rhs)) => concatenate(heap, location, lhs, rhs),
         _ => Err(format!(
             "+ is unsupported for {} and {}",
             lhs_value.type_name(),
@@ -21,7 +50,7 @@ fn binary_operation_sum(lhs_value: &Value, rhs_value: &Value) -> Result<Value, B
 
 fn binary_operation_sub(lhs_value: &Value, rhs_value: &Value) -> Result<Value, Box<dyn Error>> {
     match (lhs_value, rhs_value) {
-        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs - rhs)),
+        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs.wrapping_sub(*rhs))),
         _ => Err(format!(
             "- is unsupported for {} and {}",
             lhs_value.type_name(),
@@ -33,7 +62,7 @@ fn binary_operation_sub(lhs_value: &Value, rhs_value: &Value) -> Result<Value, B
 
 fn binary_operation_mul(lhs_value: &Value, rhs_value: &Value) -> Result<Value, Box<dyn Error>> {
     match (lhs_value, rhs_value) {
-        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs * rhs)),
+        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs.wrapping_mul(*rhs))),
         _ => Err(format!(
             "* is unsupported for {} and {}",
             lhs_value.type_name(),
@@ -45,7 +74,8 @@ fn binary_operation_mul(lhs_value: &Value, rhs_value: &Value) -> Result<Value, B
 
 fn binary_operation_div(lhs_value: &Value, rhs_value: &Value) -> Result<Value, Box<dyn Error>> {
     match (lhs_value, rhs_value) {
-        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs / rhs)),
+        (Value::Integer(_), Value::Integer(0)) => Err("division by zero".into()),
+        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs.wrapping_div(*rhs))),
         _ => Err(format!(
             "/ is unsupported for {} and {}",
             lhs_value.type_name(),
@@ -57,7 +87,8 @@ fn binary_operation_div(lhs_value: &Value, rhs_value: &Value) -> Result<Value, B
 
 fn binary_operation_rem(lhs_value: &Value, rhs_value: &Value) -> Result<Value, Box<dyn Error>> {
     match (lhs_value, rhs_value) {
-        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs % rhs)),
+        (Value::Integer(_), Value::Integer(0)) => Err("remainder of division by zero".into()),
+        (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Integer(lhs.wrapping_rem(*rhs))),
         _ => Err(format!(
             "% is unsupported for {} and {}",
             lhs_value.type_name(),
@@ -119,11 +150,13 @@ fn binary_operation_eq(lhs_value: &Value, rhs_value: &Value) -> Result<Value, Bo
     match (lhs_value, rhs_value) {
         (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Boolean(lhs == rhs)),
         (Value::Boolean(lhs), Value::Boolean(rhs)) => Ok(Value::Boolean(lhs == rhs)),
-        (Value::String(lhs), Value::String(rhs)) => Ok(Value::Boolean(lhs == rhs)),
-        (Value::Tuple(lhs_first, lhs_second), Value::Tuple(rhs_first, rhs_second)) => {
+        (Value::String(lhs), Value::String(rhs)) => {
+            Ok(Value::Boolean(lhs.as_str() == rhs.as_str()))
+        }
+        (Value::Tuple(lhs), Value::Tuple(rhs)) => {
             match (
-                binary_operation_eq(lhs_first, rhs_first)?,
-                binary_operation_eq(lhs_second, rhs_second)?,
+                binary_operation_eq(&lhs.0, &rhs.0)?,
+                binary_operation_eq(&lhs.1, &rhs.1)?,
             ) {
                 (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(a && b)),
                 _ => Ok(Value::Boolean(false)),
@@ -142,11 +175,13 @@ fn binary_operation_neq(lhs_value: &Value, rhs_value: &Value) -> Result<Value, B
     match (lhs_value, rhs_value) {
         (Value::Integer(lhs), Value::Integer(rhs)) => Ok(Value::Boolean(lhs != rhs)),
         (Value::Boolean(lhs), Value::Boolean(rhs)) => Ok(Value::Boolean(lhs != rhs)),
-        (Value::String(lhs), Value::String(rhs)) => Ok(Value::Boolean(lhs != rhs)),
-        (Value::Tuple(lhs_first, lhs_second), Value::Tuple(rhs_first, rhs_second)) => {
+        (Value::String(lhs), Value::String(rhs)) => {
+            Ok(Value::Boolean(lhs.as_str() != rhs.as_str()))
+        }
+        (Value::Tuple(lhs), Value::Tuple(rhs)) => {
             match (
-                binary_operation_neq(lhs_first, rhs_first)?,
-                binary_operation_neq(lhs_second, rhs_second)?,
+                binary_operation_neq(&lhs.0, &rhs.0)?,
+                binary_operation_neq(&lhs.1, &rhs.1)?,
             ) {
                 (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(a && b)),
                 _ => Ok(Value::Boolean(false)),
@@ -185,23 +220,48 @@ fn binary_operation_or(lhs_value: &Value, rhs_value: &Value) -> Result<Value, Bo
     }
 }
 
-pub fn binary_operation(context: &Context, t: &ast::Binary) -> Result<Value, Box<dyn Error>> {
-    let lhs = eval_term(context, &t.lhs)?;
-    let rhs = eval_term(context, &t.rhs)?;
-
-    match t.op {
-        ast::BinaryOperator::Add => binary_operation_sum(&lhs, &rhs),
-        ast::BinaryOperator::Sub => binary_operation_sub(&lhs, &rhs),
-        ast::BinaryOperator::Mul => binary_operation_mul(&lhs, &rhs),
-        ast::BinaryOperator::Div => binary_operation_div(&lhs, &rhs),
-        ast::BinaryOperator::Rem => binary_operation_rem(&lhs, &rhs),
-        ast::BinaryOperator::Eq => binary_operation_eq(&lhs, &rhs),
-        ast::BinaryOperator::Neq => binary_operation_neq(&lhs, &rhs),
-        ast::BinaryOperator::Gt => binary_operation_gt(&lhs, &rhs),
-        ast::BinaryOperator::Gte => binary_operation_gte(&lhs, &rhs),
-        ast::BinaryOperator::Lt => binary_operation_lt(&lhs, &rhs),
-        ast::BinaryOperator::Lte => binary_operation_lte(&lhs, &rhs),
-        ast::BinaryOperator::And => binary_operation_and(&lhs, &rhs),
-        ast::BinaryOperator::Or => binary_operation_or(&lhs, &rhs),
+/// Applies `op` to the values of the operands of a binary term at
+/// `location`, allocating its result in `heap`.
+pub fn apply_binary_operator(
+    heap: &Heap,
+    location: &ast::Location,
+    op: &ast::BinaryOperator,
+    lhs: &Value,
+    rhs: &Value,
+) -> Result<Value, Box<dyn Error>> {
+    match op {
+        ast::BinaryOperator::Add => binary_operation_sum(heap, location, lhs, rhs),
+        ast::BinaryOperator::Sub => binary_operation_sub(lhs, rhs),
+        ast::BinaryOperator::Mul => binary_operation_mul(lhs, rhs),
+        ast::BinaryOperator::Div => binary_operation_div(lhs, rhs),
+        ast::BinaryOperator::Rem => binary_operation_rem(lhs, rhs),
+        ast::BinaryOperator::Eq => binary_operation_eq(lhs, rhs),
+        ast::BinaryOperator::Neq => binary_operation_neq(lhs, rhs),
+        ast::BinaryOperator::Gt => binary_operation_gt(lhs, rhs),
+        ast::BinaryOperator::Gte => binary_operation_gte(lhs, rhs),
+        ast::BinaryOperator::Lt => binary_operation_lt(lhs, rhs),
+        ast::BinaryOperator::Lte => binary_operation_lte(lhs, rhs),
+        ast::BinaryOperator::And => binary_operation_and(lhs, rhs),
+        ast::BinaryOperator::Or => binary_operation_or(lhs, rhs),
+    }
+}
+
+pub fn binary_operation(
+    state: &mut State,
+    context: &Context,
+    t: &ast::Binary,
+) -> Result<Value, Box<dyn Error>> {
+    let lhs = eval_term(state, context, &t.lhs)?;
+
+    // The right side of `&&` and `||` is only evaluated when the left one
+    // doesn't decide the result.
+    match (&t.op, &lhs) {
+        (ast::BinaryOperator::And, Value::Boolean(false)) => return Ok(lhs),
+        (ast::BinaryOperator::Or, Value::Boolean(true)) => return Ok(lhs),
+        _ => {}
     }
+
+    let rhs = eval_term(state, context, &t.rhs)?;
+
+    apply_binary_operator(&state.heap, &t.location, &t.op, &lhs, &rhs)
 }
@@ -2,11 +2,11 @@ use std::error::Error;
 
 use crate::ast;
 
-use super::{eval_term, value::Value, Context};
+use super::{eval_term, hook, value::Value, Context, State};
 
 fn get_function_callee(context: &Context, term: &ast::Term) -> Result<String, Box<dyn Error>> {
     if let ast::Term::Var(ast::Variable { text, .. }) = term {
-        if let Some(Value::Function(_)) = context.get(text) {
+        if let Some(Value::Function(_) | Value::Native(_)) = context.get(text) {
             return Ok(text.clone());
         }
     }
@@ -14,7 +14,11 @@ fn get_function_callee(context: &Context, term: &ast::Term) -> Result<String, Bo
     Err("Function callee is not valid".into())
 }
 
-pub fn call_function(context: &Context, t: &ast::Call) -> Result<Value, Box<dyn Error>> {
+pub fn call_function(
+    state: &mut State,
+    context: &Context,
+    t: &ast::Call,
+) -> Result<Value, Box<dyn Error>> {
     let ast::Call {
         callee, arguments, ..
     } = t;
@@ -22,9 +26,11 @@ pub fn call_function(context: &Context, t: &ast::Call) -> Result<Value, Box<dyn
     let callee_name = get_function_callee(context, callee)?;
 
     match context.get(&callee_name).cloned() {
-        Some(Value::Function(ast::Function {
-            parameters, value, ..
-        })) => {
+        Some(Value::Function(function)) => {
+            let ast::Function {
+                parameters, value, ..
+            } = &*function;
+
             if parameters.len() > arguments.len() {
                 return Err(format!(
                     "'{}' expected {} arguments, but got {}",
@@ -37,17 +43,91 @@ pub fn call_function(context: &Context, t: &ast::Call) -> Result<Value, Box<dyn
 
             let evaled_args = arguments
                 .iter()
-                .map(|arg| eval_term(context, &arg.clone()))
+                .map(|arg| eval_term(state, context, &arg.clone()))
                 .collect::<Result<Vec<_>, _>>()?;
 
-            let call_context = parameters
+            let call = hook::Call {
+                name: &callee_name,
+                function: Some(&function),
+                arguments: &evaled_args,
+                location: &t.location,
+            };
+
+            // Hooks only see calls that get to run, so every call they enter
+            // is exited.
+            state.limits.enter_call(&t.location)?;
+
+            if let Some(hook) = state.hook.as_deref_mut() {
+                hook.enter_call(&call)?;
+            }
+
+            let memo_key = state
+                .memo
+                .as_ref()
+                .and_then(|memo| memo.key(&callee_name, &evaled_args));
+
+            let cached = memo_key
+                .as_ref()
+                .and_then(|key| state.memo.as_mut()?.get(key));
+
+            let result = match cached {
+                Some(value) => Ok(value),
+                None => {
+                    let call_context = parameters
+                        .iter()
+                        .zip(evaled_args.iter())
+                        .fold(context.clone(), |ctx, (parameter, argument)| {
+                            ctx.add(&parameter.text, argument)
+                        });
+
+                    let result = eval_term(state, &call_context, value);
+
+                    if let (Ok(result), Some(key), Some(memo)) =
+                        (&result, memo_key, state.memo.as_mut())
+                    {
+                        memo.insert(key, result);
+                    }
+
+                    result
+                }
+            };
+
+            state.limits.exit_call();
+
+            if let Some(hook) = state.hook.as_deref_mut() {
+                hook.exit_call(&call, &result)?;
+            }
+
+            result
+        }
+        Some(Value::Native(native)) => {
+            let evaled_args = arguments
                 .iter()
-                .zip(evaled_args.iter())
-                .fold(context.clone(), |ctx, (parameter, argument)| {
-                    ctx.add(&parameter.text, argument)
-                });
+                .map(|arg| eval_term(state, context, arg))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let call = hook::Call {
+                name: &callee_name,
+                function: None,
+                arguments: &evaled_args,
+                location: &t.location,
+            };
+
+            state.limits.enter_call(&t.location)?;
+
+            if let Some(hook) = state.hook.as_deref_mut() {
+                hook.enter_call(&call)?;
+            }
+
+            let result = native.call(&evaled_args);
+
+            state.limits.exit_call();
+
+            if let Some(hook) = state.hook.as_deref_mut() {
+                hook.exit_call(&call, &result)?;
+            }
 
-            eval_term(&call_context, &value)
+            result
         }
         Some(_) => Err(format!("'{}' is not callable", callee_name).into()),
         _ => Err(format!("'{}' does not exist", callee_name).into()),
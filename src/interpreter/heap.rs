@@ -0,0 +1,105 @@
+use std::{cell::Cell, ops::Deref, rc::Rc};
+
+use crate::ast;
+
+use super::limits::{Limit, LimitExceeded};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoryStats {
+    /// Approximate bytes held by strings and tuples when evaluation ended.
+    pub live: usize,
+    /// Highest value `live` reached during evaluation.
+    pub peak: usize,
+}
+
+type Usage = Rc<Cell<MemoryStats>>;
+
+/// The memory held by the strings and tuples of one evaluation. Each value
+/// gives its bytes back to the evaluation that allocated it when dropped, so
+/// evaluations don't see each other's values, even after they end.
+#[derive(Default)]
+pub struct Heap {
+    usage: Usage,
+    max: Option<usize>,
+}
+
+impl Heap {
+    pub fn new(max: Option<usize>) -> Self {
+        Self {
+            usage: Usage::default(),
+            max,
+        }
+    }
+
+    pub fn usage(&self) -> MemoryStats {
+        self.usage.get()
+    }
+
+    /// Fails when allocating `bytes` more would go over the limit, before
+    /// anything is allocated.
+    pub fn reserve(&self, bytes: usize, location: &ast::Location) -> Result<(), LimitExceeded> {
+        match self.max {
+            Some(max) if self.usage().live.saturating_add(bytes) > max => Err(LimitExceeded {
+                limit: Limit::Memory(max),
+                location: location.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn allocate<T>(&self, value: T, bytes: usize) -> Rc<Allocated<T>> {
+        let mut stats = self.usage();
+
+        stats.live += bytes;
+        stats.peak = stats.peak.max(stats.live);
+
+        self.usage.set(stats);
+
+        Rc::new(Allocated {
+            value,
+            bytes,
+            usage: Some(self.usage.clone()),
+        })
+    }
+}
+
+/// The contents of a string or tuple value, accounted for in the memory usage
+/// of the evaluation that allocated it while alive. Values share their
+/// contents through an `Rc` when cloned, so they are only counted once.
+/// Values built by the host don't belong to any evaluation.
+#[derive(Debug)]
+pub struct Allocated<T> {
+    value: T,
+    bytes: usize,
+    usage: Option<Usage>,
+}
+
+impl<T> Allocated<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            bytes: 0,
+            usage: None,
+        }
+    }
+}
+
+impl<T> Deref for Allocated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Drop for Allocated<T> {
+    fn drop(&mut self) {
+        if let Some(usage) = &self.usage {
+            let mut stats = usage.get();
+
+            stats.live -= self.bytes;
+
+            usage.set(stats);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::error::Error;
+
+use crate::ast;
+
+use super::{value::Value, Context};
+
+/// A function call as seen by a [`Hook`].
+pub struct Call<'a> {
+    /// The name the function was called through.
+    pub name: &'a str,
+    /// The called function, or `None` for native functions.
+    pub function: Option<&'a ast::Function>,
+    pub arguments: &'a [Value],
+    /// Where the call happens.
+    pub location: &'a ast::Location,
+}
+
+/// Observes evaluation, for debuggers, profilers and the like.
+///
+/// Every method does nothing by default. An error returned from any of them
+/// stops evaluation with that error.
+pub trait Hook {
+    /// Called before `term` is evaluated in `context`.
+    fn enter_term(&mut self, _term: &ast::Term, _context: &Context) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called once the arguments of a call are evaluated, before its body.
+    fn enter_call(&mut self, _call: &Call) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called when a call entered with [`Hook::enter_call`] returns or fails.
+    fn exit_call(
+        &mut self,
+        _call: &Call,
+        _result: &Result<Value, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called when a `let` binds `name`, before evaluating the rest of it.
+    fn bind(&mut self, _name: &ast::Variable, _value: &Value) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl<H: Hook + ?Sized> Hook for &mut H {
+    fn enter_term(&mut self, term: &ast::Term, context: &Context) -> Result<(), Box<dyn Error>> {
+        (**self).enter_term(term, context)
+    }
+
+    fn enter_call(&mut self, call: &Call) -> Result<(), Box<dyn Error>> {
+        (**self).enter_call(call)
+    }
+
+    fn exit_call(
+        &mut self,
+        call: &Call,
+        result: &Result<Value, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        (**self).exit_call(call, result)
+    }
+
+    fn bind(&mut self, name: &ast::Variable, value: &Value) -> Result<(), Box<dyn Error>> {
+        (**self).bind(name, value)
+    }
+}
+
+/// Runs several hooks in order, stopping at the first error.
+impl<H: Hook> Hook for Vec<H> {
+    fn enter_term(&mut self, term: &ast::Term, context: &Context) -> Result<(), Box<dyn Error>> {
+        self.iter_mut()
+            .try_for_each(|hook| hook.enter_term(term, context))
+    }
+
+    fn enter_call(&mut self, call: &Call) -> Result<(), Box<dyn Error>> {
+        self.iter_mut().try_for_each(|hook| hook.enter_call(call))
+    }
+
+    fn exit_call(
+        &mut self,
+        call: &Call,
+        result: &Result<Value, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.iter_mut()
+            .try_for_each(|hook| hook.exit_call(call, result))
+    }
+
+    fn bind(&mut self, name: &ast::Variable, value: &Value) -> Result<(), Box<dyn Error>> {
+        self.iter_mut().try_for_each(|hook| hook.bind(name, value))
+    }
+}
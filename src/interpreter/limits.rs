@@ -0,0 +1,108 @@
+use std::{error::Error, fmt::Display, time::Instant};
+
+use crate::ast;
+
+use super::Options;
+
+// Reading the clock on every step would dominate the cost of small terms.
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Steps(u64),
+    Depth(usize),
+    Deadline,
+    Memory(usize),
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Steps(steps) => write!(f, "exceeded the limit of {steps} evaluation steps"),
+            Limit::Depth(depth) => write!(f, "exceeded the maximum call depth of {depth}"),
+            Limit::Deadline => write!(f, "exceeded the evaluation deadline"),
+            Limit::Memory(bytes) => write!(f, "exceeded the memory limit of {bytes} bytes"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LimitExceeded {
+    pub limit: Limit,
+    pub location: ast::Location,
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {}:{}..{}",
+            self.limit, self.location.filename, self.location.start, self.location.end
+        )
+    }
+}
+
+impl Error for LimitExceeded {}
+
+#[derive(Default)]
+pub struct Limits {
+    max_steps: Option<u64>,
+    max_depth: Option<usize>,
+    deadline: Option<Instant>,
+    steps: u64,
+    depth: usize,
+}
+
+impl Limits {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            max_steps: options.max_steps,
+            max_depth: options.max_depth,
+            deadline: options.deadline,
+            ..Self::default()
+        }
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn step(&mut self, location: &ast::Location) -> Result<(), LimitExceeded> {
+        self.steps += 1;
+
+        if let Some(max_steps) = self.max_steps.filter(|max_steps| self.steps > *max_steps) {
+            return Err(LimitExceeded {
+                limit: Limit::Steps(max_steps),
+                location: location.clone(),
+            });
+        }
+
+        if let Some(deadline) = self.deadline {
+            if self.steps % DEADLINE_CHECK_INTERVAL == 1 && Instant::now() >= deadline {
+                return Err(LimitExceeded {
+                    limit: Limit::Deadline,
+                    location: location.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn enter_call(&mut self, location: &ast::Location) -> Result<(), LimitExceeded> {
+        if let Some(max_depth) = self.max_depth.filter(|max_depth| self.depth >= *max_depth) {
+            return Err(LimitExceeded {
+                limit: Limit::Depth(max_depth),
+                location: location.clone(),
+            });
+        }
+
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    pub fn exit_call(&mut self) {
+        self.depth -= 1;
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::{HashMap, HashSet};
+
+use super::value::Value;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    String(String),
+    Integer(i32),
+    Tuple(Box<Key>, Box<Key>),
+    Boolean(bool),
+}
+
+impl Key {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(value) => Some(Key::String(value.to_string())),
+            Value::Integer(value) => Some(Key::Integer(*value)),
+            Value::Tuple(tuple) => Some(Key::Tuple(
+                Box::new(Key::from_value(&tuple.0)?),
+                Box::new(Key::from_value(&tuple.1)?),
+            )),
+            Value::Boolean(value) => Some(Key::Boolean(*value)),
+            Value::Function(..) | Value::Native(..) => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+pub type CallKey = (String, Vec<Key>);
+
+pub struct Memo {
+    pure_functions: HashSet<String>,
+    cache: HashMap<CallKey, Value>,
+    stats: MemoStats,
+}
+
+impl Memo {
+    pub fn new(pure_functions: HashSet<String>) -> Self {
+        Self {
+            pure_functions,
+            cache: HashMap::new(),
+            stats: MemoStats::default(),
+        }
+    }
+
+    pub fn key(&self, function_name: &str, arguments: &[Value]) -> Option<CallKey> {
+        if !self.pure_functions.contains(function_name) {
+            return None;
+        }
+
+        let arguments = arguments
+            .iter()
+            .map(Key::from_value)
+            .collect::<Option<Vec<_>>>()?;
+
+        Some((function_name.to_string(), arguments))
+    }
+
+    pub fn get(&mut self, key: &CallKey) -> Option<Value> {
+        let value = self.cache.get(key).cloned();
+
+        if value.is_some() {
+            self.stats.hits += 1;
+        }
+
+        value
+    }
+
+    pub fn insert(&mut self, key: CallKey, value: &Value) {
+        self.stats.misses += 1;
+
+        self.cache.insert(key, value.clone());
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+}
@@ -1,21 +1,40 @@
-use std::{collections::HashMap, error::Error};
+use std::{collections::HashMap, error::Error, rc::Rc, time::Instant};
 
-use crate::ast;
+use crate::{ast, opt::pure_functions, source::SourceMap};
 
 mod binary_operation;
 mod call;
 mod condition;
+mod conversions;
+mod heap;
+pub mod hook;
+mod limits;
+mod memo;
+pub mod output;
 mod print;
 mod tuple;
 mod value;
 mod variable;
 
+pub(crate) use self::{binary_operation::apply_binary_operator, heap::Heap};
+pub use self::{
+    conversions::ConversionError,
+    heap::MemoryStats,
+    hook::Hook,
+    limits::{Limit, LimitExceeded},
+    memo::MemoStats,
+    output::Output,
+    value::{NativeFunction, NativeResult, Value},
+};
+
 use self::{
     binary_operation::binary_operation,
     call::call_function,
     condition::do_if,
+    limits::Limits,
+    memo::Memo,
     print::print,
-    value::Value,
+    value::TUPLE_BYTES,
     variable::{assign_variable, get_variable_value},
 };
 
@@ -36,31 +55,270 @@ impl Context {
 
         Self { inner: new }
     }
+
+    /// Every name bound in the context, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.inner.iter().map(|(key, value)| (key.as_str(), value))
+    }
 }
 
-fn eval_term(context: &Context, term: &ast::Term) -> Result<Value, Box<dyn Error>> {
+struct State<'a> {
+    memo: Option<Memo>,
+    limits: Limits,
+    heap: Heap,
+    output: &'a mut dyn Output,
+    hook: Option<&'a mut dyn Hook>,
+}
+
+/// The default of [`Options::max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 100_000;
+
+// Evaluation recurses as deep as calls nest, so when the stack runs low it
+// goes on in a new segment instead of overflowing.
+const RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT: usize = 4 * 1024 * 1024;
+
+fn eval_term(
+    state: &mut State,
+    context: &Context,
+    term: &ast::Term,
+) -> Result<Value, Box<dyn Error>> {
+    stacker::maybe_grow(RED_ZONE, STACK_SEGMENT, || evaluate(state, context, term))
+}
+
+fn evaluate(
+    state: &mut State,
+    context: &Context,
+    term: &ast::Term,
+) -> Result<Value, Box<dyn Error>> {
+    state.limits.step(term.location())?;
+
+    if let Some(hook) = state.hook.as_deref_mut() {
+        hook.enter_term(term, context)?;
+    }
+
     match term {
-        ast::Term::If(t) => do_if(context, t),
-        ast::Term::First(t) => tuple::first(context, t),
-        ast::Term::Second(t) => tuple::second(context, t),
-        ast::Term::Print(t) => print(context, t),
-        ast::Term::Call(t) => call_function(context, t),
-        ast::Term::Let(t) => assign_variable(context, t),
+        ast::Term::If(t) => do_if(state, context, t),
+        ast::Term::First(t) => tuple::first(state, context, t),
+        ast::Term::Second(t) => tuple::second(state, context, t),
+        ast::Term::Print(t) => print(state, context, t),
+        ast::Term::Call(t) => call_function(state, context, t),
+        ast::Term::Let(t) => assign_variable(state, context, t),
         ast::Term::Var(t) => get_variable_value(context, t),
-        ast::Term::Binary(t) => binary_operation(context, t),
+        ast::Term::Binary(t) => binary_operation(state, context, t),
         ast::Term::Bool(t) => Ok(Value::Boolean(t.value)),
-        ast::Term::Function(t) => Ok(Value::Function(t.clone())),
-        ast::Term::Int(t) => Ok(Value::Integer(t.value.clone())),
-        ast::Term::Str(t) => Ok(Value::String(t.value.clone())),
-        ast::Term::Tuple(t) => Ok(Value::Tuple(
-            Box::new(eval_term(context, &t.first)?),
-            Box::new(eval_term(context, &t.second)?),
-        )),
+        ast::Term::Function(t) => Ok(Value::Function(Rc::new(t.clone()))),
+        ast::Term::Int(t) => Ok(Value::Integer(t.value)),
+        ast::Term::Str(t) => {
+            state.heap.reserve(t.value.len(), &t.location)?;
+
+            let value = t.value.clone();
+            let bytes = value.capacity();
+
+            Ok(Value::String(state.heap.allocate(value, bytes)))
+        }
+        ast::Term::Tuple(t) => {
+            let first = eval_term(state, context, &t.first)?;
+            let second = eval_term(state, context, &t.second)?;
+
+            state.heap.reserve(TUPLE_BYTES, &t.location)?;
+
+            Ok(Value::Tuple(
+                state.heap.allocate((first, second), TUPLE_BYTES),
+            ))
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Cache the results of calls to pure functions. Off by default, since
+    /// calls answered from the cache don't evaluate the function's body, and
+    /// ignored when a hook observes evaluation, which must see every call.
+    pub memoize: bool,
+    /// Maximum number of terms evaluated before giving up.
+    pub max_steps: Option<u64>,
+    /// Maximum number of nested function calls, [`DEFAULT_MAX_DEPTH`] by
+    /// default.
+    pub max_depth: Option<usize>,
+    pub deadline: Option<Instant>,
+    /// Maximum approximate number of bytes held by strings and tuples.
+    pub max_memory: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            memoize: false,
+            max_steps: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            deadline: None,
+            max_memory: None,
+        }
     }
 }
 
-pub fn eval(ast: ast::File) -> Result<(), Box<dyn Error>> {
-    eval_term(&Context::default(), &ast.expression)?;
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    pub steps: u64,
+    pub memo: MemoStats,
+    pub memory: MemoryStats,
+}
+
+fn run<'a>(
+    ast: &ast::File,
+    options: &Options,
+    output: &'a mut dyn Output,
+    hook: Option<&'a mut dyn Hook>,
+    globals: &Context,
+) -> (Result<Value, Box<dyn Error>>, Stats) {
+    let mut state = State {
+        memo: (options.memoize && hook.is_none())
+            .then(|| Memo::new(pure_functions(&ast.expression))),
+        limits: Limits::new(options),
+        heap: Heap::new(options.max_memory),
+        output,
+        hook,
+    };
+
+    let result = eval_term(&mut state, globals, &ast.expression);
+    let result = result.and_then(|value| state.output.flush().map(|()| value).map_err(Into::into));
+
+    let stats = Stats {
+        steps: state.limits.steps(),
+        memo: state.memo.map(|memo| memo.stats()).unwrap_or_default(),
+        memory: state.heap.usage(),
+    };
+
+    (result, stats)
+}
+
+/// Evaluates `ast`, writing whatever it prints to `output`. When one of the
+/// limits in `options` is hit, evaluation stops with a [`LimitExceeded`]
+/// error.
+pub fn eval(
+    ast: ast::File,
+    options: &Options,
+    output: &mut dyn Output,
+) -> Result<Stats, Box<dyn Error>> {
+    let (result, stats) = run(&ast, options, output, None, &Context::default());
 
-    Ok(())
+    result.map(|_| stats)
+}
+
+/// Like [`eval`], observing evaluation with `hook`.
+pub fn eval_with_hook(
+    ast: ast::File,
+    options: &Options,
+    output: &mut dyn Output,
+    hook: &mut dyn Hook,
+) -> Result<Stats, Box<dyn Error>> {
+    let (result, stats) = run(&ast, options, output, Some(hook), &Context::default());
+
+    result.map(|_| stats)
+}
+
+/// Like [`eval_with_hook`] when `hook` is given, returning the statistics of
+/// evaluation even when it fails.
+pub fn eval_with_stats<'a>(
+    ast: ast::File,
+    options: &Options,
+    output: &'a mut dyn Output,
+    hook: Option<&'a mut dyn Hook>,
+) -> (Result<Value, Box<dyn Error>>, Stats) {
+    run(&ast, options, output, hook, &Context::default())
+}
+
+/// The message of an evaluation error, with the locations it mentions as
+/// lines and columns in `sources`.
+pub fn describe_error(error: &(dyn Error + 'static), sources: &SourceMap) -> String {
+    match error.downcast_ref::<LimitExceeded>() {
+        Some(exceeded) => format!(
+            "{} at {}",
+            exceeded.limit,
+            sources.describe(&exceeded.location)
+        ),
+        None => error.to_string(),
+    }
+}
+
+/// An interpreter that can be embedded in a host application.
+///
+/// Globals defined with [`Interpreter::define`] and functions registered with
+/// [`Interpreter::register`] are visible to every program it evaluates.
+///
+/// ```
+/// use caramuru::interpreter::{output::Buffer, Interpreter, Options, Value};
+///
+/// let output = Buffer::default();
+/// let mut interpreter = Interpreter::new(Options::default(), output.clone());
+///
+/// interpreter.register("double", |arguments| match arguments {
+///     [Value::Integer(value)] => Ok(Value::Integer(value * 2)),
+///     _ => Err("double expects an integer".into()),
+/// });
+///
+/// let file = caramuru::parse_source("main.rinha", "print(double(21))").unwrap();
+///
+/// interpreter.eval(&file).unwrap();
+///
+/// assert_eq!(output.contents(), "42\n");
+/// ```
+pub struct Interpreter {
+    options: Options,
+    output: Box<dyn Output>,
+    hook: Option<Box<dyn Hook>>,
+    globals: Context,
+    stats: Stats,
+}
+
+impl Interpreter {
+    pub fn new(options: Options, output: impl Output + 'static) -> Self {
+        Self {
+            options,
+            output: Box::new(output),
+            hook: None,
+            globals: Context::default(),
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.globals = self.globals.add(name, &value);
+    }
+
+    pub fn register(&mut self, name: &str, function: impl Fn(&[Value]) -> NativeResult + 'static) {
+        self.define(name, Value::Native(NativeFunction::new(name, function)));
+    }
+
+    /// Observes every following evaluation with `hook`.
+    pub fn set_hook(&mut self, hook: impl Hook + 'static) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    /// Evaluates `ast` and returns the value of its expression.
+    pub fn eval(&mut self, ast: &ast::File) -> Result<Value, Box<dyn Error>> {
+        let (result, stats) = run(
+            ast,
+            &self.options,
+            self.output.as_mut(),
+            self.hook.as_deref_mut().map(|hook| hook as &mut dyn Hook),
+            &self.globals,
+        );
+
+        self.stats = stats;
+
+        result
+    }
+
+    /// Statistics of the last evaluation.
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new(Options::default(), output::Stdout::new())
+    }
 }
@@ -0,0 +1,89 @@
+use std::{
+    cell::RefCell,
+    io::{self, BufWriter, Write},
+    rc::Rc,
+};
+
+/// Destination of the lines written by `print`.
+pub trait Output {
+    fn print(&mut self, line: &str) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes to the process stdout through a buffer, which is flushed when
+/// evaluation finishes.
+pub struct Stdout {
+    writer: BufWriter<io::Stdout>,
+}
+
+impl Stdout {
+    pub fn new() -> Self {
+        Self {
+            writer: BufWriter::new(io::stdout()),
+        }
+    }
+}
+
+impl Default for Stdout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Output for Stdout {
+    fn print(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{line}")
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Collects the output in memory. Clones share the same contents, so a copy
+/// can be kept to read what was printed.
+#[derive(Clone, Default)]
+pub struct Buffer {
+    contents: Rc<RefCell<String>>,
+}
+
+impl Buffer {
+    pub fn contents(&self) -> String {
+        self.contents.borrow().clone()
+    }
+}
+
+impl Output for Buffer {
+    fn print(&mut self, line: &str) -> io::Result<()> {
+        let mut contents = self.contents.borrow_mut();
+
+        contents.push_str(line);
+        contents.push('\n');
+
+        Ok(())
+    }
+}
+
+/// Discards everything printed.
+#[derive(Clone, Copy, Default)]
+pub struct Sink;
+
+impl Output for Sink {
+    fn print(&mut self, _line: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hands every printed line to a closure.
+pub struct Callback<F: FnMut(&str)>(pub F);
+
+impl<F: FnMut(&str)> Output for Callback<F> {
+    fn print(&mut self, line: &str) -> io::Result<()> {
+        (self.0)(line);
+
+        Ok(())
+    }
+}
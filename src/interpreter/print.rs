@@ -2,10 +2,16 @@ use std::error::Error;
 
 use crate::ast;
 
-use super::{eval_term, value::Value, Context};
+use super::{eval_term, value::Value, Context, State};
 
-pub fn print(context: &Context, t: &ast::Print) -> Result<Value, Box<dyn Error>> {
-    println!("{}", eval_term(context, &t.value)?);
+pub fn print(
+    state: &mut State,
+    context: &Context,
+    t: &ast::Print,
+) -> Result<Value, Box<dyn Error>> {
+    let value = eval_term(state, context, &t.value)?;
+
+    state.output.print(&value.to_string())?;
 
     Ok(Value::Boolean(true))
 }
@@ -2,18 +2,26 @@ use std::error::Error;
 
 use crate::ast;
 
-use super::{eval_term, value::Value, Context};
+use super::{eval_term, value::Value, Context, State};
 
-pub fn first(context: &Context, t: &ast::First) -> Result<Value, Box<dyn Error>> {
-    match eval_term(context, &t.value)? {
-        Value::Tuple(first, _) => Ok(*first),
+pub fn first(
+    state: &mut State,
+    context: &Context,
+    t: &ast::First,
+) -> Result<Value, Box<dyn Error>> {
+    match eval_term(state, context, &t.value)? {
+        Value::Tuple(tuple) => Ok(tuple.0.clone()),
         _ => Err("'first' called on non-tuple".into()),
     }
 }
 
-pub fn second(context: &Context, t: &ast::Second) -> Result<Value, Box<dyn Error>> {
-    match eval_term(context, &t.value)? {
-        Value::Tuple(_, second) => Ok(*second),
+pub fn second(
+    state: &mut State,
+    context: &Context,
+    t: &ast::Second,
+) -> Result<Value, Box<dyn Error>> {
+    match eval_term(state, context, &t.value)? {
+        Value::Tuple(tuple) => Ok(tuple.1.clone()),
         _ => Err("'second' called on non-tuple".into()),
     }
 }
@@ -1,24 +1,99 @@
-use std::fmt::Display;
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    mem::size_of,
+    rc::Rc,
+};
 
 use crate::ast;
 
+use super::heap::Allocated;
+
+pub type NativeResult = Result<Value, Box<dyn Error>>;
+
+type NativeFn = dyn Fn(&[Value]) -> NativeResult;
+
+/// A function implemented by the host, callable from Rinha code like any
+/// other function. It receives the evaluated arguments of the call.
+#[derive(Clone)]
+pub struct NativeFunction {
+    name: String,
+    function: Rc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(name: &str, function: impl Fn(&[Value]) -> NativeResult + 'static) -> Self {
+        Self {
+            name: name.to_string(),
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn call(&self, arguments: &[Value]) -> NativeResult {
+        (self.function)(arguments)
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A value produced by evaluating a Rinha program.
+///
+/// Values convert to and from Rust types with `From` and `TryFrom`, and to
+/// and from any serde format, following this mapping:
+///
+/// | Rinha    | Rust          | JSON                    |
+/// |----------|---------------|-------------------------|
+/// | integer  | `i32`         | number                  |
+/// | string   | `String`      | string                  |
+/// | boolean  | `bool`        | `true` / `false`        |
+/// | tuple    | `(A, B)`      | array of two elements   |
+/// | function | -             | rejected                |
+///
+/// Tuples nest, so `(1, (2, 3))` maps to `[1, [2, 3]]`. Numbers outside of
+/// the `i32` range, fractional numbers, `null`, objects and arrays with any
+/// other number of elements fail to deserialize.
+///
+/// Strings, tuples and functions are shared when a value is cloned, which
+/// makes cloning cheap: values are immutable, so nothing can tell.
 #[derive(Debug, Clone)]
 pub enum Value {
-    String(String),
+    String(Rc<Allocated<String>>),
     Integer(i32),
-    Tuple(Box<Value>, Box<Value>),
+    Tuple(Rc<Allocated<(Value, Value)>>),
     Boolean(bool),
-    Function(ast::Function),
+    Function(Rc<ast::Function>),
+    Native(NativeFunction),
 }
 
+/// The bytes accounted for each tuple allocated by an evaluation.
+pub const TUPLE_BYTES: usize = 2 * size_of::<Value>();
+
 impl Value {
+    pub fn string(value: String) -> Self {
+        Value::String(Rc::new(Allocated::new(value)))
+    }
+
+    pub fn tuple(first: Value, second: Value) -> Self {
+        Value::Tuple(Rc::new(Allocated::new((first, second))))
+    }
+
     pub fn type_name(&self) -> &str {
         match self {
             Value::Tuple(..) => "tuple",
             Value::String(..) => "string",
             Value::Boolean(..) => "boolean",
             Value::Integer(..) => "integer",
-            Value::Function(..) => "function",
+            Value::Function(..) | Value::Native(..) => "function",
         }
     }
 }
@@ -28,9 +103,9 @@ impl Display for Value {
         match self {
             Value::Integer(value) => f.write_fmt(format_args!("{value}")),
             Value::Boolean(value) => f.write_fmt(format_args!("{value}")),
-            Value::String(value) => f.write_fmt(format_args!("{value}")),
-            Value::Tuple(first, second) => f.write_fmt(format_args!("({first}, {second})")),
-            Value::Function(..) => f.write_fmt(format_args!("#function")),
+            Value::String(value) => f.write_fmt(format_args!("{}", value.as_str())),
+            Value::Tuple(tuple) => f.write_fmt(format_args!("({}, {})", tuple.0, tuple.1)),
+            Value::Function(..) | Value::Native(..) => f.write_fmt(format_args!("#function")),
         }
     }
 }
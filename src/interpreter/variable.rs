@@ -2,7 +2,7 @@ use std::error::Error;
 
 use crate::ast;
 
-use super::{eval_term, value::Value, Context};
+use super::{eval_term, value::Value, Context, State};
 
 pub fn get_variable_value(context: &Context, t: &ast::Variable) -> Result<Value, Box<dyn Error>> {
     context
@@ -11,10 +11,18 @@ pub fn get_variable_value(context: &Context, t: &ast::Variable) -> Result<Value,
         .ok_or(format!("'{}' does not exist", t.text).into())
 }
 
-pub fn assign_variable(context: &Context, t: &ast::Let) -> Result<Value, Box<dyn Error>> {
-    let value = eval_term(context, &t.value)?;
+pub fn assign_variable(
+    state: &mut State,
+    context: &Context,
+    t: &ast::Let,
+) -> Result<Value, Box<dyn Error>> {
+    let value = eval_term(state, context, &t.value)?;
+
+    if let Some(hook) = state.hook.as_deref_mut() {
+        hook.bind(&t.name, &value)?;
+    }
 
     let updated_context = context.add(&t.name.text, &value);
 
-    eval_term(&updated_context, &t.next)
+    eval_term(state, &updated_context, &t.next)
 }
@@ -1,15 +1,47 @@
 pub mod ast;
+pub mod coverage;
+pub mod debug;
+pub mod fmt;
 pub mod interpreter;
+pub mod lsp;
+pub mod opt;
+pub mod parse;
+pub mod profile;
+mod protocol;
+pub mod source;
+pub mod trace;
 
-use std::fs::read_to_string;
+use std::{fs::read_to_string, path::Path};
 
 use ast::File;
 use miette::IntoDiagnostic;
-use rinha::parser::parse_or_report;
+use source::SourceMap;
 
-pub fn parse_file(file_path: &String) -> miette::Result<File> {
-    read_to_string(file_path)
-        .into_diagnostic()
-        .and_then(|source| parse_or_report(file_path, &source).into_diagnostic())
-        .map(|file_node| File::from(file_node))
+pub fn parse_source(file_name: &str, source: &str) -> miette::Result<File> {
+    Ok(parse::parse(file_name, source)?)
+}
+
+/// Reads a Rinha program from `file_path`. Files with a `.json` extension are
+/// read as a JSON AST, anything else as source code.
+pub fn parse_file(file_path: &str) -> miette::Result<File> {
+    load_file(file_path, &mut SourceMap::new())
+}
+
+/// Like [`parse_file`], adding the source code to `sources`. The source code
+/// of a JSON AST isn't known, so nothing is added for it.
+pub fn load_file(file_path: &str, sources: &mut SourceMap) -> miette::Result<File> {
+    let source = read_to_string(file_path).into_diagnostic()?;
+
+    if Path::new(file_path)
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::from_str(&source).into_diagnostic()
+    } else {
+        let file = parse_source(file_path, &source)?;
+
+        sources.add(file_path, source);
+
+        Ok(file)
+    }
 }
@@ -0,0 +1,279 @@
+use std::{collections::HashSet, fmt};
+
+use crate::ast::{self, visit::Visitor};
+
+/// What little can be known about a value without running the program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Type {
+    Unknown,
+    Integer,
+    String,
+    Boolean,
+    Tuple(Box<Type>, Box<Type>),
+    Function {
+        parameters: Vec<String>,
+        result: Box<Type>,
+    },
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Unknown => write!(f, "?"),
+            Type::Integer => write!(f, "integer"),
+            Type::String => write!(f, "string"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Tuple(first, second) => write!(f, "({first}, {second})"),
+            Type::Function { parameters, result } => {
+                write!(f, "fn ({}) => {result}", parameters.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Let,
+    Parameter,
+}
+
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub name: String,
+    pub kind: Kind,
+    /// Where the name is written in the binding.
+    pub location: ast::Location,
+    pub ty: Type,
+}
+
+#[derive(Clone, Debug)]
+pub struct Reference {
+    pub location: ast::Location,
+    /// The index of the binding the name refers to, if it is in scope.
+    pub binding: Option<usize>,
+}
+
+/// The bindings of a file and what each name refers to.
+///
+/// Names are resolved lexically, although the interpreter scopes them
+/// dynamically: a function body sees the names bound where it is called. So
+/// a name in a function body which isn't in scope is only reported as
+/// unbound when nothing in the file binds it.
+#[derive(Debug, Default)]
+pub struct Analysis {
+    pub bindings: Vec<Binding>,
+    pub references: Vec<Reference>,
+    pub unbound: Vec<ast::Variable>,
+}
+
+impl Analysis {
+    pub fn new(file: &ast::File) -> Self {
+        let mut names = BoundNames::default();
+
+        names.visit_term(&file.expression);
+
+        let mut resolver = Resolver {
+            analysis: Analysis::default(),
+            scope: Vec::new(),
+            functions: 0,
+            names: names.0,
+        };
+
+        resolver.term(&file.expression);
+
+        resolver.analysis
+    }
+
+    /// The binding whose name, or a reference to it, is at `offset`.
+    pub fn binding_at(&self, offset: usize) -> Option<&Binding> {
+        let contains = |location: &ast::Location| (location.start..=location.end).contains(&offset);
+
+        let index = self
+            .references
+            .iter()
+            .find(|reference| contains(&reference.location))
+            .and_then(|reference| reference.binding)
+            .or_else(|| {
+                self.bindings
+                    .iter()
+                    .position(|binding| contains(&binding.location))
+            })?;
+
+        Some(&self.bindings[index])
+    }
+}
+
+#[derive(Default)]
+struct BoundNames(HashSet<String>);
+
+impl Visitor<'_> for BoundNames {
+    fn visit_binding(&mut self, t: &ast::Variable) {
+        self.0.insert(t.text.clone());
+    }
+}
+
+struct Resolver {
+    analysis: Analysis,
+    /// The names in scope, with the index of their binding.
+    scope: Vec<(String, usize)>,
+    /// How many function bodies the current term is in.
+    functions: usize,
+    names: HashSet<String>,
+}
+
+impl Resolver {
+    fn bind(&mut self, name: &ast::Variable, kind: Kind) -> usize {
+        self.analysis.bindings.push(Binding {
+            name: name.text.clone(),
+            kind,
+            location: name.location.clone(),
+            ty: Type::Unknown,
+        });
+
+        self.analysis.bindings.len() - 1
+    }
+
+    fn reference(&mut self, name: &ast::Variable) -> Type {
+        let binding = self
+            .scope
+            .iter()
+            .rev()
+            .find(|(text, _)| *text == name.text)
+            .map(|(_, index)| *index);
+
+        self.analysis.references.push(Reference {
+            location: name.location.clone(),
+            binding,
+        });
+
+        match binding {
+            Some(index) => self.analysis.bindings[index].ty.clone(),
+            None => {
+                if self.functions == 0 || !self.names.contains(&name.text) {
+                    self.analysis.unbound.push(name.clone());
+                }
+
+                Type::Unknown
+            }
+        }
+    }
+
+    fn term(&mut self, term: &ast::Term) -> Type {
+        match term {
+            ast::Term::Int(..) => Type::Integer,
+            ast::Term::Str(..) => Type::String,
+            ast::Term::Bool(..) => Type::Boolean,
+            ast::Term::Var(t) => self.reference(t),
+            ast::Term::Let(t) => {
+                let scope = self.scope.len();
+                let index = self.bind(&t.name, Kind::Let);
+
+                // Only a function can refer to its own name, as it is called
+                // after the binding.
+                let recursive = matches!(*t.value, ast::Term::Function(..));
+
+                if recursive {
+                    self.scope.push((t.name.text.clone(), index));
+                }
+
+                let ty = self.term(&t.value);
+
+                self.analysis.bindings[index].ty = ty;
+
+                if !recursive {
+                    self.scope.push((t.name.text.clone(), index));
+                }
+
+                let ty = self.term(&t.next);
+
+                self.scope.truncate(scope);
+
+                ty
+            }
+            ast::Term::Function(t) => {
+                let scope = self.scope.len();
+
+                for parameter in &t.parameters {
+                    let index = self.bind(parameter, Kind::Parameter);
+
+                    self.scope.push((parameter.text.clone(), index));
+                }
+
+                self.functions += 1;
+
+                let result = self.term(&t.value);
+
+                self.functions -= 1;
+                self.scope.truncate(scope);
+
+                Type::Function {
+                    parameters: t
+                        .parameters
+                        .iter()
+                        .map(|parameter| parameter.text.clone())
+                        .collect(),
+                    result: Box::new(result),
+                }
+            }
+            ast::Term::Call(t) => {
+                let callee = self.term(&t.callee);
+
+                for argument in &t.arguments {
+                    self.term(argument);
+                }
+
+                match callee {
+                    Type::Function { result, .. } => *result,
+                    _ => Type::Unknown,
+                }
+            }
+            ast::Term::Binary(t) => {
+                let lhs = self.term(&t.lhs);
+                let rhs = self.term(&t.rhs);
+
+                match t.op {
+                    ast::BinaryOperator::Add => match (lhs, rhs) {
+                        (Type::Integer, Type::Integer) => Type::Integer,
+                        (Type::String, _) | (_, Type::String) => Type::String,
+                        _ => Type::Unknown,
+                    },
+                    ast::BinaryOperator::Sub
+                    | ast::BinaryOperator::Mul
+                    | ast::BinaryOperator::Div
+                    | ast::BinaryOperator::Rem => Type::Integer,
+                    _ => Type::Boolean,
+                }
+            }
+            ast::Term::If(t) => {
+                self.term(&t.condition);
+
+                let then = self.term(&t.then);
+                let otherwise = self.term(&t.otherwise);
+
+                if then == otherwise {
+                    then
+                } else {
+                    Type::Unknown
+                }
+            }
+            ast::Term::Tuple(t) => Type::Tuple(
+                Box::new(self.term(&t.first)),
+                Box::new(self.term(&t.second)),
+            ),
+            ast::Term::First(t) => match self.term(&t.value) {
+                Type::Tuple(first, _) => *first,
+                _ => Type::Unknown,
+            },
+            ast::Term::Second(t) => match self.term(&t.value) {
+                Type::Tuple(_, second) => *second,
+                _ => Type::Unknown,
+            },
+            ast::Term::Print(t) => {
+                self.term(&t.value);
+
+                // `print` returns true, not its argument.
+                Type::Boolean
+            }
+        }
+    }
+}
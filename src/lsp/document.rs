@@ -0,0 +1,116 @@
+use miette::Diagnostic;
+use serde_json::{json, Value as Json};
+
+use crate::{ast, parse::parse, source::Source};
+
+use super::analysis::Analysis;
+
+// Severities of the Language Server Protocol.
+const ERROR: u64 = 1;
+const WARNING: u64 = 2;
+
+/// An open document, analyzed each time it changes.
+pub struct Document {
+    pub source: Source,
+    /// The program, unless it doesn't parse.
+    pub file: Option<ast::File>,
+    pub analysis: Analysis,
+    pub diagnostics: Vec<Json>,
+}
+
+impl Document {
+    pub fn new(name: &str, text: String) -> Self {
+        let mut document = Self {
+            source: Source::new(text),
+            file: None,
+            analysis: Analysis::default(),
+            diagnostics: Vec::new(),
+        };
+
+        match parse(name, document.source.text()) {
+            Ok(file) => {
+                document.analysis = Analysis::new(&file);
+                document.diagnostics = document
+                    .analysis
+                    .unbound
+                    .iter()
+                    .map(|name| {
+                        json!({
+                            "range": document.range(name.location.start, name.location.end),
+                            "severity": WARNING,
+                            "source": "rinha",
+                            "message": format!("`{}` is not bound", name.text),
+                        })
+                    })
+                    .collect();
+                document.file = Some(file);
+            }
+            Err(error) => {
+                document.diagnostics = error
+                    .errors()
+                    .iter()
+                    .map(|error| document.diagnostic(error))
+                    .collect();
+            }
+        }
+
+        document
+    }
+
+    /// Converts a byte offset to a position in UTF-16 code units, which is
+    /// what clients count by default.
+    pub fn position(&self, offset: usize) -> Json {
+        let position = self.source.position(offset);
+        let start = self
+            .source
+            .line_range(position.line)
+            .map_or(0, |range| range.start);
+        let offset = offset.min(self.source.text().len());
+
+        json!({
+            "line": position.line - 1,
+            "character": self.source.text()[start..offset].encode_utf16().count(),
+        })
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Json {
+        json!({ "start": self.position(start), "end": self.position(end) })
+    }
+
+    pub fn offset(&self, position: &Json) -> Option<usize> {
+        let line = position["line"].as_u64()? as usize + 1;
+        let character = position["character"].as_u64()? as usize;
+        let range = self.source.line_range(line)?;
+        let mut units = 0;
+
+        for (index, char) in self.source.text()[range.clone()].char_indices() {
+            if units >= character {
+                return Some(range.start + index);
+            }
+
+            units += char.len_utf16();
+        }
+
+        Some(range.end)
+    }
+
+    fn diagnostic(&self, error: &dyn Diagnostic) -> Json {
+        let span = error
+            .labels()
+            .and_then(|mut labels| labels.next())
+            .map_or(0..0, |label| label.offset()..label.offset() + label.len());
+
+        let message = match error.help() {
+            Some(help) => format!("{error}\n{help}"),
+            None => error.to_string(),
+        };
+
+        json!({
+            "range": self.range(span.start, span.end),
+            "severity": ERROR,
+            "code": error.code().map(|code| code.to_string()),
+            "source": "rinha",
+            "message": message,
+        })
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{BufRead, Write},
+};
+
+use serde_json::{json, Value as Json};
+
+use crate::{
+    ast,
+    fmt::{format_file, has_comments, DEFAULT_WIDTH},
+    protocol::{read_message, write_message},
+};
+
+mod analysis;
+mod document;
+
+use self::{
+    analysis::{Kind, Type},
+    document::Document,
+};
+
+// Error codes of JSON-RPC and the Language Server Protocol.
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const REQUEST_FAILED: i64 = -32803;
+
+// Kinds of document symbols.
+const FUNCTION: u64 = 12;
+const VARIABLE: u64 = 13;
+
+type RequestError = (i64, String);
+
+struct Server<W> {
+    output: W,
+    documents: HashMap<String, Document>,
+    shut_down: bool,
+}
+
+impl<W: Write> Server<W> {
+    fn send(&mut self, message: Json) -> Result<(), Box<dyn Error>> {
+        Ok(write_message(&mut self.output, &message)?)
+    }
+
+    fn document(&self, params: &Json) -> Result<&Document, RequestError> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        self.documents
+            .get(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("unknown document '{uri}'")))
+    }
+
+    fn open(&mut self, uri: &str, text: String) -> Result<(), Box<dyn Error>> {
+        let name = uri.rsplit('/').next().unwrap_or(uri);
+        let document = Document::new(name, text);
+        let diagnostics = document.diagnostics.clone();
+
+        self.documents.insert(uri.to_string(), document);
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn publish_diagnostics(
+        &mut self,
+        uri: &str,
+        diagnostics: Vec<Json>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Result<(), Box<dyn Error>> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+
+                self.open(uri, text.to_string())?;
+            }
+            "textDocument/didChange" => {
+                // Documents are synchronized in full, so the last change has
+                // the whole text.
+                let change = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last());
+
+                if let Some(text) = change.and_then(|change| change["text"].as_str()) {
+                    self.open(uri, text.to_string())?;
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                self.publish_diagnostics(uri, Vec::new())?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, RequestError> {
+        if self.shut_down {
+            return Err((INVALID_REQUEST, "the server was shut down".to_string()));
+        }
+
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "positionEncoding": "utf-16",
+                    "textDocumentSync": { "openClose": true, "change": 1 },
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "documentSymbolProvider": true,
+                    "documentFormattingProvider": true,
+                },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shut_down = true;
+
+                Ok(Json::Null)
+            }
+            "textDocument/definition" => {
+                let document = self.document(params)?;
+                let binding = document
+                    .offset(&params["position"])
+                    .and_then(|offset| document.analysis.binding_at(offset));
+
+                Ok(match binding {
+                    Some(binding) => json!({
+                        "uri": params["textDocument"]["uri"],
+                        "range": document.range(binding.location.start, binding.location.end),
+                    }),
+                    None => Json::Null,
+                })
+            }
+            "textDocument/hover" => {
+                let document = self.document(params)?;
+                let binding = document
+                    .offset(&params["position"])
+                    .and_then(|offset| document.analysis.binding_at(offset));
+
+                let Some(binding) = binding else {
+                    return Ok(Json::Null);
+                };
+
+                let signature = match &binding.ty {
+                    Type::Unknown => binding.name.clone(),
+                    ty => format!("{}: {ty}", binding.name),
+                };
+                let kind = match binding.kind {
+                    Kind::Let => "let",
+                    Kind::Parameter => "parameter",
+                };
+                let line = document.source.position(binding.location.start).line;
+
+                Ok(json!({
+                    "contents": {
+                        "kind": "markdown",
+                        "value": format!("```rinha\n{signature}\n```\n{kind} bound on line {line}"),
+                    },
+                }))
+            }
+            "textDocument/documentSymbol" => {
+                let document = self.document(params)?;
+                let mut symbols = Vec::new();
+                let mut term = document.file.as_ref().map(|file| &file.expression);
+
+                while let Some(ast::Term::Let(t)) = term {
+                    let kind = match *t.value {
+                        ast::Term::Function(..) => FUNCTION,
+                        _ => VARIABLE,
+                    };
+
+                    symbols.push(json!({
+                        "name": t.name.text,
+                        "kind": kind,
+                        "range": document.range(t.location.start, t.location.end),
+                        "selectionRange": document.range(t.name.location.start, t.name.location.end),
+                    }));
+
+                    term = Some(&t.next);
+                }
+
+                Ok(json!(symbols))
+            }
+            "textDocument/formatting" => {
+                let document = self.document(params)?;
+                let text = document.source.text();
+
+                let Some(file) = &document.file else {
+                    return Err((REQUEST_FAILED, "the document has syntax errors".to_string()));
+                };
+
+                if has_comments(text) {
+                    return Err((
+                        REQUEST_FAILED,
+                        "formatting would remove the comments".to_string(),
+                    ));
+                }
+
+                let formatted = format_file(file, DEFAULT_WIDTH);
+
+                Ok(if formatted == text {
+                    json!([])
+                } else {
+                    json!([{ "range": document.range(0, text.len()), "newText": formatted }])
+                })
+            }
+            method => Err((METHOD_NOT_FOUND, format!("unsupported method '{method}'"))),
+        }
+    }
+}
+
+/// Serves the Language Server Protocol, reading messages from `input` and
+/// writing to `output`, until the client asks the server to exit.
+pub fn serve(mut input: impl BufRead, output: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut server = Server {
+        output,
+        documents: HashMap::new(),
+        shut_down: false,
+    };
+
+    while let Some(message) = read_message(&mut input)? {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+
+        if method == "exit" {
+            break;
+        }
+
+        // Responses to requests from the server have no method, and the
+        // server makes none.
+        if method.is_empty() {
+            continue;
+        }
+
+        if message.get("id").is_none() {
+            server.notification(method, params)?;
+
+            continue;
+        }
+
+        let response = match server.request(method, params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": message["id"], "result": result }),
+            Err((code, error)) => json!({
+                "jsonrpc": "2.0",
+                "id": message["id"],
+                "error": { "code": code, "message": error },
+            }),
+        };
+
+        server.send(response)?;
+    }
+
+    Ok(())
+}
@@ -1,11 +1,392 @@
-use std::{env::args, error::Error};
+use std::{
+    error::Error,
+    fs::{read_to_string, write},
+    io::{stderr, stdin, stdout, BufReader, BufWriter},
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant},
+};
 
-use caramuru::{interpreter::eval, parse_file};
+use caramuru::{
+    coverage::Coverage,
+    debug::{self, Debugger, Prompt, Session},
+    fmt::{format_file, has_comments, DEFAULT_WIDTH},
+    interpreter::{
+        describe_error, eval_with_stats,
+        output::{Callback, Stdout},
+        Hook, Interpreter, Options, DEFAULT_MAX_DEPTH,
+    },
+    load_file, lsp,
+    opt::optimize,
+    parse_file, parse_source,
+    profile::Profiler,
+    source::SourceMap,
+    trace::{TraceOptions, Tracer},
+};
+use clap::{Args, Parser, Subcommand};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let source_file_path = args().nth(1).ok_or("You need to provide a file name")?;
+const STACK_SIZE: usize = 1 << 30;
 
-    let file = parse_file(&source_file_path)?;
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    eval(file)
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the AST of a program
+    Ast(AstArgs),
+
+    /// Format Rinha source files in place
+    Fmt(FmtArgs),
+
+    /// Evaluate a program step by step with an interactive prompt
+    Debug(DebugArgs),
+
+    /// Serve the Debug Adapter Protocol over stdin and stdout, for editors
+    Dap,
+
+    /// Serve the Language Server Protocol over stdin and stdout, for editors
+    Lsp,
+}
+
+#[derive(Args)]
+struct AstArgs {
+    /// Path to the Rinha source or JSON AST file
+    file: String,
+
+    /// Print the AST as JSON, in the same format as the Rinha reference parser
+    #[arg(long)]
+    json: bool,
+
+    /// Indent the JSON output
+    #[arg(long, requires = "json")]
+    pretty: bool,
+
+    /// Run the optimizer before printing
+    #[arg(short = 'O', long)]
+    optimize: bool,
+}
+
+#[derive(Args)]
+struct FmtArgs {
+    /// Paths to the Rinha source files
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Don't write the files, fail if any of them isn't formatted
+    #[arg(long)]
+    check: bool,
+
+    /// Maximum line width
+    #[arg(long, default_value_t = DEFAULT_WIDTH)]
+    width: usize,
+
+    /// Format files with comments, which are removed
+    #[arg(long)]
+    strip_comments: bool,
+}
+
+#[derive(Args)]
+struct DebugArgs {
+    /// Path to the Rinha source or JSON AST file
+    file: String,
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Path to the Rinha source or JSON AST file
+    #[arg(required = true)]
+    file: Option<String>,
+
+    /// Run the optimizer before evaluating
+    #[arg(short = 'O', long)]
+    optimize: bool,
+
+    /// Print the optimized AST instead of evaluating it
+    #[arg(long)]
+    dump_optimized: bool,
+
+    /// Cache the results of calls to pure functions
+    #[arg(long)]
+    memo: bool,
+
+    /// Print memoization cache hits and misses to stderr at exit
+    #[arg(long)]
+    memo_stats: bool,
+
+    /// Stop after evaluating this many terms
+    #[arg(long, value_name = "STEPS")]
+    max_steps: Option<u64>,
+
+    /// Stop when function calls nest deeper than this
+    #[arg(long, value_name = "DEPTH", default_value_t = DEFAULT_MAX_DEPTH)]
+    max_depth: usize,
+
+    /// Stop evaluating after this long, e.g. "500ms" or "2s"
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Stop when strings and tuples hold more than this many bytes, e.g. "64M"
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_memory: Option<usize>,
+
+    /// Print the peak memory held by strings and tuples to stderr at exit
+    #[arg(long)]
+    memory_stats: bool,
+
+    /// Print the calls and time spent in each function to stderr at exit
+    #[arg(long)]
+    profile: bool,
+
+    /// Write the time spent in each call stack to a file, in the folded
+    /// format read by flame graph tools
+    #[arg(long, value_name = "FILE")]
+    profile_folded: Option<String>,
+
+    /// Print every step of evaluation to stderr
+    #[arg(long)]
+    trace: bool,
+
+    /// Only trace calls of this function
+    #[arg(long, value_name = "NAME", requires = "trace")]
+    trace_function: Option<String>,
+
+    /// Don't trace calls nested deeper than this
+    #[arg(long, value_name = "DEPTH", requires = "trace")]
+    trace_depth: Option<usize>,
+
+    /// Write which functions, branches and lines were evaluated to a file,
+    /// in the lcov format
+    #[arg(long, value_name = "FILE")]
+    coverage: Option<String>,
+
+    /// Print the source code to stderr at exit, marking what was never
+    /// evaluated
+    #[arg(long)]
+    coverage_listing: bool,
+}
+
+fn parse_size(size: &str) -> Result<usize, String> {
+    let (digits, multiplier) = match size.char_indices().last() {
+        Some((index, 'K' | 'k')) => (&size[..index], 1 << 10),
+        Some((index, 'M' | 'm')) => (&size[..index], 1 << 20),
+        Some((index, 'G' | 'g')) => (&size[..index], 1 << 30),
+        _ => (size, 1),
+    };
+
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{size}'"))
+}
+
+fn print_ast(args: AstArgs) -> Result<(), Box<dyn Error>> {
+    let file = parse_file(&args.file)?;
+    let file = if args.optimize { optimize(file) } else { file };
+
+    if args.pretty {
+        println!("{}", serde_json::to_string_pretty(&file)?);
+    } else if args.json {
+        println!("{}", serde_json::to_string(&file)?);
+    } else {
+        println!("{:#?}", file);
+    }
+
+    Ok(())
+}
+
+fn format(args: FmtArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let mut unformatted = false;
+
+    for path in &args.files {
+        let source = read_to_string(path)?;
+
+        if has_comments(&source) && !args.strip_comments {
+            return Err(format!(
+                "{path} has comments, which formatting would remove; \
+                 pass --strip-comments to format it anyway"
+            )
+            .into());
+        }
+
+        let formatted = format_file(&parse_source(path, &source)?, args.width);
+
+        if formatted == source {
+            continue;
+        }
+
+        if args.check {
+            println!("{path} is not formatted");
+            unformatted = true;
+        } else {
+            write(path, formatted)?;
+        }
+    }
+
+    Ok(if unformatted {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn debug(args: DebugArgs) -> Result<(), Box<dyn Error>> {
+    let mut sources = SourceMap::new();
+    let file = load_file(&args.file, &mut sources)?;
+
+    let mut interpreter = Interpreter::new(
+        Options::default(),
+        Callback(|line: &str| println!("{line}")),
+    );
+
+    interpreter.set_hook(Debugger::new(
+        Session::new(sources.get(&args.file).cloned()),
+        Prompt::new(stdin().lock(), stdout()),
+    ));
+
+    let value = interpreter
+        .eval(&file)
+        .map_err(|error| describe_error(error.as_ref(), &sources))?;
+
+    println!("evaluated to {value}");
+
+    Ok(())
+}
+
+fn run(args: RunArgs) -> Result<(), Box<dyn Error>> {
+    let path = args.file.ok_or("You need to provide a file name")?;
+    let mut sources = SourceMap::new();
+    let file = load_file(&path, &mut sources)?;
+
+    if args.dump_optimized {
+        println!("{:#?}", optimize(file));
+
+        return Ok(());
+    }
+
+    let file = if args.optimize { optimize(file) } else { file };
+
+    let options = Options {
+        memoize: args.memo,
+        max_steps: args.max_steps,
+        max_depth: Some(args.max_depth),
+        deadline: args.timeout.map(|timeout| Instant::now() + timeout),
+        max_memory: args.max_memory,
+    };
+
+    let mut profiler = (args.profile || args.profile_folded.is_some()).then(Profiler::new);
+    let mut tracer = if args.trace {
+        Some(Tracer::new(
+            BufWriter::new(stderr()),
+            sources.clone(),
+            TraceOptions {
+                function: args.trace_function,
+                max_depth: args.trace_depth,
+            },
+        ))
+    } else {
+        None
+    };
+
+    let covering = args.coverage.is_some() || args.coverage_listing;
+    let mut coverage = covering.then(Coverage::new);
+    let covered = covering.then(|| file.clone());
+
+    let mut hooks = Vec::<&mut dyn Hook>::new();
+
+    hooks.extend(profiler.as_mut().map(|profiler| profiler as &mut dyn Hook));
+    hooks.extend(tracer.as_mut().map(|tracer| tracer as &mut dyn Hook));
+    hooks.extend(coverage.as_mut().map(|coverage| coverage as &mut dyn Hook));
+
+    let hook = (!hooks.is_empty()).then_some(&mut hooks as &mut dyn Hook);
+    let (result, stats) = eval_with_stats(file, &options, &mut Stdout::new(), hook);
+
+    if let Some(profiler) = &mut profiler {
+        let profile = profiler.finish();
+
+        if args.profile {
+            eprint!("{}", profile.table(&sources));
+        }
+
+        if let Some(folded) = args.profile_folded {
+            write(folded, profile.folded(&sources))?;
+        }
+    }
+
+    if let (Some(coverage), Some(covered)) = (coverage, covered) {
+        let report = coverage.report(&covered);
+        let source = sources.get(&path).ok_or("coverage needs the source code")?;
+
+        if let Some(lcov) = args.coverage {
+            write(lcov, report.lcov(&path, source))?;
+        }
+
+        if args.coverage_listing {
+            eprint!("{}", report.listing(source));
+        }
+    }
+
+    if args.memo_stats {
+        eprintln!(
+            "memo: {} hits, {} misses",
+            stats.memo.hits, stats.memo.misses
+        );
+    }
+
+    if args.memory_stats {
+        eprintln!("memory: {} bytes peak", stats.memory.peak);
+    }
+
+    result
+        .map(|_| ())
+        .map_err(|error| describe_error(error.as_ref(), &sources).into())
+}
+
+fn dispatch(cli: Cli) -> Result<ExitCode, Box<dyn Error>> {
+    match cli.command {
+        Some(Command::Ast(args)) => print_ast(args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Fmt(args)) => format(args),
+        Some(Command::Debug(args)) => debug(args).map(|()| ExitCode::SUCCESS),
+        Some(Command::Dap) => {
+            debug::serve(BufReader::new(stdin()), stdout()).map(|()| ExitCode::SUCCESS)
+        }
+        Some(Command::Lsp) => lsp::serve(stdin().lock(), stdout()).map(|()| ExitCode::SUCCESS),
+        None => run(cli.run).map(|()| ExitCode::SUCCESS),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // Evaluation grows its own stack, but printing, comparing and dropping
+    // values recurse as deep as tuples nest, which would overflow the main
+    // thread's stack on lists of a few thousand elements. The memory is only
+    // used as the stack grows.
+    let result = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| dispatch(cli).map_err(|error| format!("{error:?}")))
+        .expect("failed to start the main thread")
+        .join()
+        .unwrap_or_else(|_| Err("the interpreter panicked".to_string()));
+
+    match result {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("Error: {error}");
+
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{
+        self,
+        fold::{walk_binary, walk_function, Fold},
+    },
+    interpreter::{apply_binary_operator, Heap, Value},
+};
+
+use super::query::{contains_call, is_boolean, is_free_in, is_integer, is_literal};
+
+type Constants = HashMap<String, ast::Term>;
+
+fn literal_value(term: &ast::Term) -> Option<Value> {
+    match term {
+        ast::Term::Int(t) => Some(Value::Integer(t.value)),
+        ast::Term::Str(t) => Some(Value::string(t.value.clone())),
+        ast::Term::Bool(t) => Some(Value::Boolean(t.value)),
+        _ => None,
+    }
+}
+
+fn value_literal(value: Value, location: ast::Location) -> Option<ast::Term> {
+    match value {
+        Value::Integer(value) => Some(ast::Term::Int(ast::Integer { value, location })),
+        Value::String(value) => Some(ast::Term::Str(ast::Str {
+            value: value.to_string(),
+            location,
+        })),
+        Value::Boolean(value) => Some(ast::Term::Bool(ast::Boolean { value, location })),
+        _ => None,
+    }
+}
+
+fn with_location(term: &ast::Term, location: ast::Location) -> ast::Term {
+    match term {
+        ast::Term::Int(t) => ast::Term::Int(ast::Integer {
+            value: t.value,
+            location,
+        }),
+        ast::Term::Str(t) => ast::Term::Str(ast::Str {
+            value: t.value.clone(),
+            location,
+        }),
+        ast::Term::Bool(t) => ast::Term::Bool(ast::Boolean {
+            value: t.value,
+            location,
+        }),
+        term => term.clone(),
+    }
+}
+
+fn is_int_literal(term: &ast::Term, expected: i32) -> bool {
+    matches!(term, ast::Term::Int(ast::Integer { value, .. }) if *value == expected)
+}
+
+fn is_bool_literal(term: &ast::Term, expected: bool) -> bool {
+    matches!(term, ast::Term::Bool(ast::Boolean { value, .. }) if *value == expected)
+}
+
+fn overflows(op: &ast::BinaryOperator, lhs: &Value, rhs: &Value) -> bool {
+    let (Value::Integer(lhs), Value::Integer(rhs)) = (lhs, rhs) else {
+        return false;
+    };
+
+    match op {
+        ast::BinaryOperator::Add => lhs.checked_add(*rhs).is_none(),
+        ast::BinaryOperator::Sub => lhs.checked_sub(*rhs).is_none(),
+        ast::BinaryOperator::Mul => lhs.checked_mul(*rhs).is_none(),
+        ast::BinaryOperator::Div => lhs.checked_div(*rhs).is_none(),
+        ast::BinaryOperator::Rem => lhs.checked_rem(*rhs).is_none(),
+        _ => false,
+    }
+}
+
+fn simplify_binary(t: ast::Binary) -> ast::Term {
+    // Operations that fail, like division by zero, or that wrap around are
+    // left for the program to run into, as written.
+    if let (Some(lhs), Some(rhs)) = (literal_value(&t.lhs), literal_value(&t.rhs)) {
+        let folded = apply_binary_operator(&Heap::default(), &t.location, &t.op, &lhs, &rhs)
+            .ok()
+            .filter(|_| !overflows(&t.op, &lhs, &rhs))
+            .and_then(|value| value_literal(value, t.location.clone()));
+
+        if let Some(term) = folded {
+            return term;
+        }
+    }
+
+    let ast::Binary { lhs, op, rhs, .. } = &t;
+
+    let simplified = match op {
+        ast::BinaryOperator::Add if is_integer(lhs) && is_int_literal(rhs, 0) => Some(lhs),
+        ast::BinaryOperator::Add if is_int_literal(lhs, 0) && is_integer(rhs) => Some(rhs),
+        ast::BinaryOperator::Sub if is_integer(lhs) && is_int_literal(rhs, 0) => Some(lhs),
+        ast::BinaryOperator::Mul if is_integer(lhs) && is_int_literal(rhs, 1) => Some(lhs),
+        ast::BinaryOperator::Mul if is_int_literal(lhs, 1) && is_integer(rhs) => Some(rhs),
+        ast::BinaryOperator::Div if is_integer(lhs) && is_int_literal(rhs, 1) => Some(lhs),
+        ast::BinaryOperator::And if is_boolean(lhs) && is_bool_literal(rhs, true) => Some(lhs),
+        ast::BinaryOperator::And if is_bool_literal(lhs, true) && is_boolean(rhs) => Some(rhs),
+        ast::BinaryOperator::Or if is_boolean(lhs) && is_bool_literal(rhs, false) => Some(lhs),
+        ast::BinaryOperator::Or if is_bool_literal(lhs, false) && is_boolean(rhs) => Some(rhs),
+        _ => None,
+    };
+
+    match simplified {
+        Some(term) => *term.clone(),
+        None => ast::Term::Binary(t),
+    }
+}
+
+#[derive(Default)]
+struct ConstantFolder {
+    constants: Constants,
+}
+
+impl Fold for ConstantFolder {
+    fn fold_var(&mut self, t: ast::Variable) -> ast::Term {
+        match self.constants.get(&t.text) {
+            Some(literal) => with_location(literal, t.location),
+            None => ast::Term::Var(t),
+        }
+    }
+
+    fn fold_binary(&mut self, t: ast::Binary) -> ast::Term {
+        match walk_binary(self, t) {
+            ast::Term::Binary(t) => simplify_binary(t),
+            term => term,
+        }
+    }
+
+    fn fold_if(&mut self, t: ast::If) -> ast::Term {
+        match self.fold_term(*t.condition) {
+            ast::Term::Bool(ast::Boolean { value: true, .. }) => self.fold_term(*t.then),
+            ast::Term::Bool(ast::Boolean { value: false, .. }) => self.fold_term(*t.otherwise),
+            condition => ast::Term::If(ast::If {
+                condition: Box::new(condition),
+                then: Box::new(self.fold_term(*t.then)),
+                otherwise: Box::new(self.fold_term(*t.otherwise)),
+                location: t.location,
+            }),
+        }
+    }
+
+    fn fold_let(&mut self, t: ast::Let) -> ast::Term {
+        let value = self.fold_term(*t.value);
+
+        let constants = self.constants.clone();
+
+        if is_literal(&value) {
+            self.constants.insert(t.name.text.clone(), value.clone());
+        } else {
+            self.constants.remove(&t.name.text);
+        }
+
+        let next = self.fold_term(*t.next);
+
+        self.constants = constants;
+
+        // Functions see the bindings of their call site, so the binding
+        // can only go away when no call could observe it.
+        if is_literal(&value) && !is_free_in(&t.name.text, &next) && !contains_call(&next) {
+            return next;
+        }
+
+        ast::Term::Let(ast::Let {
+            value: Box::new(value),
+            next: Box::new(next),
+            ..t
+        })
+    }
+
+    // Free variables of a function body are resolved in the context of
+    // each call, not where the function is defined, so the constants in
+    // scope here must not leak into it.
+    fn fold_function(&mut self, t: ast::Function) -> ast::Term {
+        let constants = std::mem::take(&mut self.constants);
+        let function = walk_function(self, t);
+
+        self.constants = constants;
+
+        function
+    }
+
+    fn fold_first(&mut self, t: ast::First) -> ast::Term {
+        match self.fold_term(*t.value) {
+            ast::Term::Tuple(tuple) if is_literal(&tuple.first) && is_literal(&tuple.second) => {
+                with_location(&tuple.first, t.location)
+            }
+            value => ast::Term::First(ast::First {
+                value: Box::new(value),
+                location: t.location,
+            }),
+        }
+    }
+
+    fn fold_second(&mut self, t: ast::Second) -> ast::Term {
+        match self.fold_term(*t.value) {
+            ast::Term::Tuple(tuple) if is_literal(&tuple.first) && is_literal(&tuple.second) => {
+                with_location(&tuple.second, t.location)
+            }
+            value => ast::Term::Second(ast::Second {
+                value: Box::new(value),
+                location: t.location,
+            }),
+        }
+    }
+}
+
+pub fn fold_constants(term: ast::Term) -> ast::Term {
+    ConstantFolder::default().fold_term(term)
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+use crate::ast::{
+    self,
+    fold::{walk_function, walk_term, Fold},
+};
+
+use super::query::{collect_names, contains_call, count_bindings, is_free_in, size};
+
+const INLINE_THRESHOLD: usize = 24;
+
+#[derive(Clone)]
+struct Candidate {
+    function: ast::Function,
+    // Whether the `let` binding it encloses the rest of the program.
+    global: bool,
+}
+
+// Calls are resolved by name in the context of the call site, so a function
+// is only inlined when its name is bound exactly once in the whole program:
+// any call to that name inside the `let` can then only reach that function.
+struct Inliner {
+    bindings: HashMap<String, usize>,
+    names: HashSet<String>,
+    candidates: HashMap<String, Candidate>,
+    temporaries: usize,
+    top_level: bool,
+}
+
+impl Inliner {
+    fn new(term: &ast::Term) -> Self {
+        let mut bindings = HashMap::new();
+        let mut names = HashSet::new();
+
+        count_bindings(term, &mut bindings);
+        collect_names(term, &mut names);
+
+        Self {
+            bindings,
+            names,
+            candidates: HashMap::new(),
+            temporaries: 0,
+            top_level: true,
+        }
+    }
+
+    fn is_inlinable(&self, name: &str, function: &ast::Function) -> bool {
+        self.bindings.get(name) == Some(&1)
+            && !is_free_in(name, &function.value)
+            && size(&function.value) <= INLINE_THRESHOLD
+    }
+
+    fn temporary(&mut self, parameter: &str) -> String {
+        loop {
+            let name = format!("{}_{}", parameter, self.temporaries);
+
+            self.temporaries += 1;
+
+            if self.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    // Turns `(fn (a, b) => body)(x, y)` into `let a = x; let b = y; body`.
+    // Arguments are evaluated before any parameter is bound, so they go
+    // through temporaries whenever a later argument could observe an
+    // earlier parameter.
+    fn beta_reduce(
+        &mut self,
+        function: ast::Function,
+        arguments: Vec<ast::Term>,
+        location: ast::Location,
+    ) -> ast::Term {
+        let needs_temporaries = arguments
+            .iter()
+            .enumerate()
+            .skip(1)
+            .any(|(index, argument)| {
+                contains_call(argument)
+                    || function.parameters[..index]
+                        .iter()
+                        .any(|parameter| is_free_in(&parameter.text, argument))
+            });
+
+        let bind = |name: ast::Variable, value: ast::Term, next: ast::Term| {
+            ast::Term::Let(ast::Let {
+                name,
+                value: Box::new(value),
+                next: Box::new(next),
+                location: location.clone(),
+            })
+        };
+
+        if !needs_temporaries {
+            return function
+                .parameters
+                .into_iter()
+                .zip(arguments)
+                .rev()
+                .fold(*function.value, |next, (parameter, argument)| {
+                    bind(parameter, argument, next)
+                });
+        }
+
+        let temporaries = function
+            .parameters
+            .iter()
+            .map(|parameter| ast::Variable {
+                text: self.temporary(&parameter.text),
+                location: parameter.location.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let body = function
+            .parameters
+            .into_iter()
+            .zip(temporaries.iter().cloned())
+            .rev()
+            .fold(*function.value, |next, (parameter, temporary)| {
+                bind(parameter, ast::Term::Var(temporary), next)
+            });
+
+        temporaries
+            .into_iter()
+            .zip(arguments)
+            .rev()
+            .fold(body, |next, (temporary, argument)| {
+                bind(temporary, argument, next)
+            })
+    }
+}
+
+impl Fold for Inliner {
+    // Only the `let`s reached from the root through other `let`s enclose the
+    // rest of the program.
+    fn fold_term(&mut self, term: ast::Term) -> ast::Term {
+        if let ast::Term::Let(t) = term {
+            return self.fold_let(t);
+        }
+
+        let top_level = mem::replace(&mut self.top_level, false);
+        let term = walk_term(self, term);
+
+        self.top_level = top_level;
+
+        term
+    }
+
+    fn fold_let(&mut self, t: ast::Let) -> ast::Term {
+        let top_level = mem::replace(&mut self.top_level, false);
+        let value = self.fold_term(*t.value);
+
+        self.top_level = top_level;
+
+        let candidate = match &value {
+            ast::Term::Function(function) if self.is_inlinable(&t.name.text, function) => {
+                Some(Candidate {
+                    function: function.clone(),
+                    global: top_level,
+                })
+            }
+            _ => None,
+        };
+
+        let is_candidate = candidate.is_some();
+
+        if let Some(candidate) = candidate {
+            self.candidates.insert(t.name.text.clone(), candidate);
+        }
+
+        let next = self.fold_term(*t.next);
+
+        if is_candidate {
+            self.candidates.remove(&t.name.text);
+        }
+
+        ast::Term::Let(ast::Let {
+            value: Box::new(value),
+            next: Box::new(next),
+            ..t
+        })
+    }
+
+    // A function can be called once the `let`s around it are done, where
+    // their names are no longer bound, unless they enclose the rest of the
+    // program.
+    fn fold_function(&mut self, t: ast::Function) -> ast::Term {
+        let candidates = self.candidates.clone();
+
+        self.candidates.retain(|_, candidate| candidate.global);
+
+        let function = walk_function(self, t);
+
+        self.candidates = candidates;
+
+        function
+    }
+
+    fn fold_call(&mut self, t: ast::Call) -> ast::Term {
+        let callee = self.fold_term(*t.callee);
+        let arguments = t
+            .arguments
+            .into_iter()
+            .map(|argument| Box::new(self.fold_term(*argument)))
+            .collect::<Vec<_>>();
+
+        // Calls to anything but a name fail at runtime, so they stay.
+        let function = match &callee {
+            ast::Term::Var(variable) => self
+                .candidates
+                .get(&variable.text)
+                .map(|candidate| candidate.function.clone()),
+            _ => None,
+        };
+
+        match function {
+            Some(function) if function.parameters.len() == arguments.len() => {
+                let arguments = arguments.into_iter().map(|argument| *argument).collect();
+
+                self.beta_reduce(function, arguments, t.location)
+            }
+            _ => ast::Term::Call(ast::Call {
+                callee: Box::new(callee),
+                arguments,
+                location: t.location,
+            }),
+        }
+    }
+}
+
+/// Inlines small non-recursive functions at the call sites where their name
+/// can only resolve to the `let` that binds them, beta-reducing the call.
+///
+/// The interpreter rejects calls whose callee is not a name, and calls to a
+/// function after the `let` binding it is done. Both are left as written,
+/// since inlining would make them succeed.
+pub fn inline_functions(term: ast::Term) -> ast::Term {
+    Inliner::new(&term).fold_term(term)
+}
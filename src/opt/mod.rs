@@ -0,0 +1,16 @@
+use crate::ast;
+
+mod fold;
+mod inline;
+mod purity;
+mod query;
+
+pub use self::{fold::fold_constants, inline::inline_functions, purity::pure_functions};
+
+pub fn optimize(file: ast::File) -> ast::File {
+    let expression = fold_constants(file.expression);
+    let expression = inline_functions(expression);
+    let expression = fold_constants(expression);
+
+    ast::File { expression, ..file }
+}
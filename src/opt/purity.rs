@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{
+    self,
+    visit::{walk_let, Visitor},
+};
+
+use super::query::count_bindings;
+
+struct FunctionCollector<'a> {
+    functions: Vec<(&'a str, &'a ast::Function)>,
+}
+
+impl<'a> Visitor<'a> for FunctionCollector<'a> {
+    fn visit_let(&mut self, t: &'a ast::Let) {
+        if let ast::Term::Function(function) = t.value.as_ref() {
+            self.functions.push((&t.name.text, function));
+        }
+
+        walk_let(self, t);
+    }
+}
+
+// Collects the names a function body depends on besides its own bindings,
+// or `None` when it prints, creates closures or calls something that is not
+// a plain name.
+fn collect_dependencies<'a>(
+    term: &'a ast::Term,
+    locals: &mut Vec<&'a str>,
+    dependencies: &mut HashSet<&'a str>,
+) -> Option<()> {
+    match term {
+        ast::Term::Print(..) | ast::Term::Function(..) => None,
+        ast::Term::Int(..) | ast::Term::Str(..) | ast::Term::Bool(..) => Some(()),
+        ast::Term::Var(t) => {
+            if !locals.contains(&t.text.as_str()) {
+                dependencies.insert(&t.text);
+            }
+
+            Some(())
+        }
+        ast::Term::Let(t) => {
+            collect_dependencies(&t.value, locals, dependencies)?;
+
+            locals.push(&t.name.text);
+
+            let result = collect_dependencies(&t.next, locals, dependencies);
+
+            locals.pop();
+
+            result
+        }
+        ast::Term::First(t) => collect_dependencies(&t.value, locals, dependencies),
+        ast::Term::Second(t) => collect_dependencies(&t.value, locals, dependencies),
+        ast::Term::Tuple(t) => {
+            collect_dependencies(&t.first, locals, dependencies)?;
+            collect_dependencies(&t.second, locals, dependencies)
+        }
+        ast::Term::Binary(t) => {
+            collect_dependencies(&t.lhs, locals, dependencies)?;
+            collect_dependencies(&t.rhs, locals, dependencies)
+        }
+        ast::Term::If(t) => {
+            collect_dependencies(&t.condition, locals, dependencies)?;
+            collect_dependencies(&t.then, locals, dependencies)?;
+            collect_dependencies(&t.otherwise, locals, dependencies)
+        }
+        ast::Term::Call(t) => {
+            match t.callee.as_ref() {
+                ast::Term::Var(callee) if !locals.contains(&callee.text.as_str()) => {
+                    dependencies.insert(&callee.text);
+                }
+                _ => return None,
+            }
+
+            t.arguments
+                .iter()
+                .try_for_each(|argument| collect_dependencies(argument, locals, dependencies))
+        }
+    }
+}
+
+/// Finds the names of the functions whose result depends only on their
+/// arguments.
+///
+/// A function is pure when it never prints, directly or through the functions
+/// it calls, and every name it reads besides its own parameters and `let`s is
+/// another pure function. Since variables are resolved where a function is
+/// called, only names bound exactly once in the whole program are considered,
+/// so that every call through that name reaches the same function.
+pub fn pure_functions(term: &ast::Term) -> HashSet<String> {
+    let mut bindings = HashMap::new();
+    let mut collector = FunctionCollector {
+        functions: Vec::new(),
+    };
+
+    count_bindings(term, &mut bindings);
+    collector.visit_term(term);
+
+    let mut candidates = collector
+        .functions
+        .into_iter()
+        .filter(|(name, _)| bindings.get(*name) == Some(&1))
+        .filter_map(|(name, function)| {
+            let mut locals = function
+                .parameters
+                .iter()
+                .map(|parameter| parameter.text.as_str())
+                .collect();
+            let mut dependencies = HashSet::new();
+
+            collect_dependencies(&function.value, &mut locals, &mut dependencies)
+                .map(|_| (name, dependencies))
+        })
+        .collect::<HashMap<_, _>>();
+
+    loop {
+        let impure = candidates
+            .iter()
+            .filter(|(_, dependencies)| {
+                dependencies
+                    .iter()
+                    .any(|dependency| !candidates.contains_key(dependency))
+            })
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+
+        if impure.is_empty() {
+            break;
+        }
+
+        for name in impure {
+            candidates.remove(name);
+        }
+    }
+
+    candidates.into_keys().map(str::to_string).collect()
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{
+    self,
+    visit::{walk_term, Visitor},
+};
+
+pub fn is_literal(term: &ast::Term) -> bool {
+    matches!(
+        term,
+        ast::Term::Int(..) | ast::Term::Str(..) | ast::Term::Bool(..)
+    )
+}
+
+#[derive(Default)]
+struct CallFinder {
+    found: bool,
+}
+
+impl Visitor<'_> for CallFinder {
+    fn visit_call(&mut self, _t: &ast::Call) {
+        self.found = true;
+    }
+}
+
+pub fn contains_call(term: &ast::Term) -> bool {
+    let mut finder = CallFinder::default();
+
+    finder.visit_term(term);
+
+    finder.found
+}
+
+pub fn is_free_in(name: &str, term: &ast::Term) -> bool {
+    match term {
+        ast::Term::Var(t) => t.text == name,
+        ast::Term::Int(..) | ast::Term::Str(..) | ast::Term::Bool(..) => false,
+        ast::Term::Function(t) => {
+            !t.parameters.iter().any(|parameter| parameter.text == name)
+                && is_free_in(name, &t.value)
+        }
+        ast::Term::Let(t) => {
+            is_free_in(name, &t.value) || (t.name.text != name && is_free_in(name, &t.next))
+        }
+        ast::Term::Call(t) => {
+            is_free_in(name, &t.callee)
+                || t.arguments
+                    .iter()
+                    .any(|argument| is_free_in(name, argument))
+        }
+        ast::Term::Print(t) => is_free_in(name, &t.value),
+        ast::Term::First(t) => is_free_in(name, &t.value),
+        ast::Term::Second(t) => is_free_in(name, &t.value),
+        ast::Term::Tuple(t) => is_free_in(name, &t.first) || is_free_in(name, &t.second),
+        ast::Term::Binary(t) => is_free_in(name, &t.lhs) || is_free_in(name, &t.rhs),
+        ast::Term::If(t) => {
+            is_free_in(name, &t.condition)
+                || is_free_in(name, &t.then)
+                || is_free_in(name, &t.otherwise)
+        }
+    }
+}
+
+pub fn is_integer(term: &ast::Term) -> bool {
+    match term {
+        ast::Term::Int(..) => true,
+        ast::Term::Binary(t) => match t.op {
+            ast::BinaryOperator::Sub
+            | ast::BinaryOperator::Mul
+            | ast::BinaryOperator::Div
+            | ast::BinaryOperator::Rem => true,
+            ast::BinaryOperator::Add => is_integer(&t.lhs) && is_integer(&t.rhs),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+pub fn is_boolean(term: &ast::Term) -> bool {
+    match term {
+        ast::Term::Bool(..) => true,
+        ast::Term::Binary(t) => !matches!(
+            t.op,
+            ast::BinaryOperator::Add
+                | ast::BinaryOperator::Sub
+                | ast::BinaryOperator::Mul
+                | ast::BinaryOperator::Div
+                | ast::BinaryOperator::Rem
+        ),
+        _ => false,
+    }
+}
+
+#[derive(Default)]
+struct Size(usize);
+
+impl Visitor<'_> for Size {
+    fn visit_term(&mut self, term: &ast::Term) {
+        self.0 += 1;
+
+        walk_term(self, term);
+    }
+}
+
+pub fn size(term: &ast::Term) -> usize {
+    let mut size = Size::default();
+
+    size.visit_term(term);
+
+    size.0
+}
+
+struct BindingCounter<'a>(&'a mut HashMap<String, usize>);
+
+impl Visitor<'_> for BindingCounter<'_> {
+    fn visit_binding(&mut self, t: &ast::Variable) {
+        *self.0.entry(t.text.clone()).or_default() += 1;
+    }
+}
+
+pub fn count_bindings(term: &ast::Term, counts: &mut HashMap<String, usize>) {
+    BindingCounter(counts).visit_term(term);
+}
+
+struct NameCollector<'a>(&'a mut HashSet<String>);
+
+impl Visitor<'_> for NameCollector<'_> {
+    fn visit_var(&mut self, t: &ast::Variable) {
+        self.0.insert(t.text.clone());
+    }
+
+    fn visit_binding(&mut self, t: &ast::Variable) {
+        self.0.insert(t.text.clone());
+    }
+}
+
+pub fn collect_names(term: &ast::Term, names: &mut HashSet<String>) {
+    NameCollector(names).visit_term(term);
+}
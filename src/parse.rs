@@ -0,0 +1,315 @@
+//! Parsing source code with the reference parser, reporting every syntax
+//! error it finds instead of the first one.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    iter, mem,
+    ops::Range,
+};
+
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use rinha::{
+    ast::{Bool, Element, Location, Term},
+    parser::InnerError,
+    rinha::FileParser,
+};
+
+use crate::ast::File;
+
+/// How deep terms can nest. Converting, formatting, optimizing and dropping
+/// the AST recurse as deep as its terms nest, so deeper programs are rejected
+/// before they can overflow the stack `main` runs them on. Each `let` nests
+/// the rest of the program, so this is well past what programs use.
+pub const MAX_NESTING: usize = 100_000;
+
+#[derive(Debug, Diagnostic)]
+pub enum SyntaxError {
+    #[diagnostic(transparent)]
+    Parser(InnerError),
+
+    #[diagnostic(
+        code(caramuru::integer_out_of_range),
+        help("integers must fit in 32 bits")
+    )]
+    IntegerOutOfRange {
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    /// A term the parser gave up on, to go on parsing the rest.
+    #[diagnostic(code(caramuru::invalid_term))]
+    InvalidTerm {
+        message: String,
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    #[diagnostic(code(caramuru::too_deep))]
+    TooDeep {
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    /// The end of the file where the parser expected nothing in particular,
+    /// which the reference parser can only report with a blank help.
+    #[diagnostic(code(zu::expected_token))]
+    UnexpectedEof {
+        #[label("here")]
+        span: SourceSpan,
+    },
+
+    /// Like [`SyntaxError::UnexpectedEof`], for a token.
+    #[diagnostic(code(zu::unrecognized_token))]
+    UnrecognizedToken {
+        #[label("here")]
+        span: SourceSpan,
+    },
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxError::Parser(error) => error.fmt(f),
+            SyntaxError::IntegerOutOfRange { .. } => write!(f, "integer literal out of range"),
+            SyntaxError::InvalidTerm { message, .. } => write!(f, "invalid term: {message}"),
+            SyntaxError::TooDeep { .. } => {
+                write!(f, "terms nest deeper than {MAX_NESTING} levels")
+            }
+            SyntaxError::UnexpectedEof { .. } => write!(f, "expected token, but got eof"),
+            SyntaxError::UnrecognizedToken { .. } => write!(f, "unrecognized token"),
+        }
+    }
+}
+
+impl Error for SyntaxError {}
+
+/// Every syntax error in a file.
+#[derive(Debug, Diagnostic)]
+pub struct ParseError {
+    name: String,
+    #[source_code]
+    source_code: NamedSource,
+    #[related]
+    errors: Vec<SyntaxError>,
+}
+
+impl ParseError {
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.len() {
+            1 => write!(f, "found a syntax error in {}", self.name),
+            count => write!(f, "found {count} syntax errors in {}", self.name),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The byte ranges of integer literals in `text` that don't fit in an `i32`,
+/// which the reference parser panics on.
+fn out_of_range_integers(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let skip_while = |mut index: usize, f: fn(u8) -> bool| {
+        while bytes.get(index).copied().is_some_and(f) {
+            index += 1;
+        }
+
+        index
+    };
+
+    let mut ranges = Vec::new();
+    let mut index = 0;
+
+    // Digits in strings, comments and names aren't integer literals.
+    while index < bytes.len() {
+        let start = index;
+
+        index = match (bytes[index], bytes.get(index + 1)) {
+            (b'"', _) => {
+                let mut end = index + 1;
+
+                while end < bytes.len() && bytes[end] != b'"' {
+                    end += if bytes[end] == b'\\' { 2 } else { 1 };
+                }
+
+                // An unterminated string doesn't lex as one.
+                if end < bytes.len() {
+                    end + 1
+                } else {
+                    index + 1
+                }
+            }
+            (b'/', Some(b'/')) => skip_while(index, |byte| byte != b'\n' && byte != b'\r'),
+            (b'/', Some(b'*')) => text[index + 2..]
+                .find("*/")
+                .map_or(index + 1, |end| index + 2 + end + 2),
+            (byte, _) if byte.is_ascii_alphabetic() => skip_while(index, |byte| {
+                byte.is_ascii_alphanumeric() || byte == b'/' || byte == b'_'
+            }),
+            (byte, _) if byte.is_ascii_digit() => {
+                let end = skip_while(index, |byte| byte.is_ascii_digit());
+
+                if text[start..end].parse::<i32>().is_err() {
+                    ranges.push(start..end);
+                }
+
+                end
+            }
+            _ => index + 1,
+        };
+    }
+
+    ranges
+}
+
+fn children(term: &mut Term) -> Vec<&mut Term> {
+    match term {
+        Term::Call(t) => iter::once(&mut *t.callee)
+            .chain(t.arguments.iter_mut())
+            .collect(),
+        Term::Binary(t) => vec![&mut *t.lhs, &mut *t.rhs],
+        Term::Function(t) => vec![&mut *t.value],
+        Term::Let(t) => vec![&mut *t.value, &mut *t.next],
+        Term::If(t) => vec![&mut *t.condition, &mut *t.then, &mut *t.otherwise],
+        Term::Print(t) => vec![&mut *t.value],
+        Term::First(t) => vec![&mut *t.value],
+        Term::Second(t) => vec![&mut *t.value],
+        Term::Tuple(t) => vec![&mut *t.first, &mut *t.second],
+        Term::Error(_) | Term::Int(_) | Term::Str(_) | Term::Bool(_) | Term::Var(_) => vec![],
+    }
+}
+
+/// The location of the first term nested deeper than [`MAX_NESTING`].
+fn too_deep(term: &mut Term) -> Option<Range<usize>> {
+    let mut terms = vec![(term, 1)];
+
+    while let Some((term, depth)) = terms.pop() {
+        if depth > MAX_NESTING {
+            return Some(term.location().start..term.location().end);
+        }
+
+        terms.extend(children(term).into_iter().map(|child| (child, depth + 1)));
+    }
+
+    None
+}
+
+fn placeholder() -> Term {
+    Term::Bool(Bool {
+        value: false,
+        location: Location::default(),
+    })
+}
+
+/// Drops `term` a level at a time, which dropping it at once would do by
+/// recursing as deep as it nests.
+fn dismantle(term: Term) {
+    let mut terms = vec![term];
+
+    while let Some(mut term) = terms.pop() {
+        terms.extend(
+            children(&mut term)
+                .into_iter()
+                .map(|child| mem::replace(child, placeholder())),
+        );
+    }
+}
+
+type LalrpopError<'a> = lalrpop_util::ParseError<usize, lalrpop_util::lexer::Token<'a>, InnerError>;
+
+fn syntax_error(error: LalrpopError) -> SyntaxError {
+    let expected = |expected: Vec<String>| match expected.split_last() {
+        Some((last, [])) => Some(format!("expected {last}")),
+        Some((last, rest)) => Some(format!("expected one of {} or {last}", rest.join(", "))),
+        None => None,
+    };
+
+    match error {
+        LalrpopError::InvalidToken { location } => SyntaxError::Parser(InnerError::InvalidToken {
+            err_span: SourceSpan::from(location..location),
+        }),
+        LalrpopError::UnrecognizedEof {
+            location,
+            expected: tokens,
+        } => {
+            let span = SourceSpan::from(location..location);
+
+            match expected(tokens) {
+                Some(help) => SyntaxError::Parser(InnerError::ExpectedToken {
+                    err_span: span,
+                    help,
+                }),
+                None => SyntaxError::UnexpectedEof { span },
+            }
+        }
+        LalrpopError::UnrecognizedToken {
+            token: (start, _, end),
+            expected: tokens,
+        } => {
+            let span = SourceSpan::from(start..end);
+
+            match expected(tokens) {
+                Some(help) => SyntaxError::Parser(InnerError::UnrecoginzedToken {
+                    err_span: span,
+                    help,
+                }),
+                None => SyntaxError::UnrecognizedToken { span },
+            }
+        }
+        LalrpopError::ExtraToken {
+            token: (start, token, end),
+        } => SyntaxError::Parser(InnerError::ExtraToken {
+            err_span: SourceSpan::from(start..end),
+            token: token.to_string(),
+        }),
+        LalrpopError::User { error } => SyntaxError::Parser(error),
+    }
+}
+
+/// Parses `text`, the source code of the file `name`.
+pub fn parse(name: &str, text: &str) -> Result<File, ParseError> {
+    let mut errors = out_of_range_integers(text)
+        .into_iter()
+        .map(|range| SyntaxError::IntegerOutOfRange { span: range.into() })
+        .collect::<Vec<_>>();
+
+    // The reference parser panics on integers out of range.
+    if errors.is_empty() {
+        // Its own report drops the errors it can't recover from.
+        let mut recovered = Vec::new();
+        let mut result = FileParser::new().parse(&mut recovered, name, text);
+
+        if let Ok(file) = &mut result {
+            if let Some(range) = too_deep(&mut file.expression) {
+                errors.push(SyntaxError::TooDeep { span: range.into() });
+                dismantle(mem::replace(&mut file.expression, placeholder()));
+            }
+        }
+
+        match result {
+            Ok(file) if recovered.is_empty() && errors.is_empty() => match File::try_from(file) {
+                Ok(file) => return Ok(file),
+                Err(invalid) => errors.extend(invalid),
+            },
+            result => errors.extend(
+                recovered
+                    .into_iter()
+                    .map(|recovery| recovery.error)
+                    .chain(result.err())
+                    .map(syntax_error),
+            ),
+        }
+    }
+
+    Err(ParseError {
+        name: name.to_string(),
+        source_code: NamedSource::new(name, text.to_string()),
+        errors,
+    })
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ast,
+    interpreter::{
+        hook::{Call, Hook},
+        Context, Value,
+    },
+    source::SourceMap,
+};
+
+#[derive(Clone, Debug)]
+pub struct FunctionProfile {
+    /// The name the function is called through, usually its `let` name.
+    pub name: String,
+    /// Where the function is defined, or `None` for native functions.
+    pub location: Option<ast::Location>,
+    pub calls: u64,
+    /// Time spent in calls of the function, including the functions it calls.
+    /// Recursive calls are only counted once.
+    pub inclusive: Duration,
+    /// Time spent in the function itself.
+    pub exclusive: Duration,
+    /// The most calls of the function in progress at once.
+    pub max_depth: usize,
+}
+
+impl FunctionProfile {
+    fn label(&self, sources: &SourceMap) -> String {
+        match &self.location {
+            Some(location) => format!("{} ({})", self.name, sources.describe(location)),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// The results of a [`Profiler`].
+#[derive(Clone, Debug)]
+pub struct Profile {
+    /// Every function called, from the most to the least exclusive time.
+    pub functions: Vec<FunctionProfile>,
+    pub total: Duration,
+    /// Exclusive time by call stack, outermost function first. The program
+    /// outside any function has an empty stack.
+    stacks: Vec<(Vec<usize>, Duration)>,
+}
+
+impl Profile {
+    /// A table of the functions, with positions taken from `sources`.
+    pub fn table(&self, sources: &SourceMap) -> String {
+        let mut rows = vec![[
+            "function".to_string(),
+            "location".to_string(),
+            "calls".to_string(),
+            "inclusive".to_string(),
+            "exclusive".to_string(),
+            "max depth".to_string(),
+        ]];
+
+        for function in &self.functions {
+            rows.push([
+                function.name.clone(),
+                function
+                    .location
+                    .as_ref()
+                    .map_or("native".to_string(), |location| sources.describe(location)),
+                function.calls.to_string(),
+                format!("{:.2?}", function.inclusive),
+                format!("{:.2?}", function.exclusive),
+                function.max_depth.to_string(),
+            ]);
+        }
+
+        let widths = (0..6)
+            .map(|column| {
+                rows.iter()
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut table = String::new();
+
+        for row in rows {
+            // Names and locations are aligned to the left, numbers to the
+            // right.
+            let line = format!(
+                "{:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {:>w4$}  {:>w5$}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                row[5],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+                w4 = widths[4],
+                w5 = widths[5],
+            );
+
+            writeln!(table, "{}", line.trim_end()).unwrap();
+        }
+
+        writeln!(table, "total {:.2?}", self.total).unwrap();
+
+        table
+    }
+
+    /// The exclusive time of each call stack in microseconds, in the folded
+    /// format read by flame graph tools.
+    pub fn folded(&self, sources: &SourceMap) -> String {
+        let labels = self
+            .functions
+            .iter()
+            .map(|function| function.label(sources).replace(';', ","))
+            .collect::<Vec<_>>();
+
+        let mut lines = self
+            .stacks
+            .iter()
+            .filter(|(_, time)| time.as_micros() > 0)
+            .map(|(stack, time)| {
+                let frames = std::iter::once("<main>")
+                    .chain(stack.iter().map(|index| labels[*index].as_str()))
+                    .collect::<Vec<_>>();
+
+                format!("{} {}", frames.join(";"), time.as_micros())
+            })
+            .collect::<Vec<_>>();
+
+        lines.sort();
+
+        lines.into_iter().map(|line| line + "\n").collect()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Key {
+    name: String,
+    definition: Option<(String, usize)>,
+}
+
+/// A [`Hook`] that measures how many times each function is called and how
+/// long the calls take.
+#[derive(Default)]
+pub struct Profiler {
+    functions: Vec<FunctionProfile>,
+    keys: HashMap<Key, usize>,
+    /// Calls in progress of each function.
+    active: Vec<usize>,
+    /// When the outermost call in progress of each function started.
+    started: Vec<Option<Instant>>,
+    stack: Vec<usize>,
+    stacks: HashMap<Vec<usize>, Duration>,
+    start: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the time since the last event to the current call stack.
+    fn tick(&mut self) -> Instant {
+        let now = Instant::now();
+
+        if let Some(last) = self.last {
+            *self.stacks.entry(self.stack.clone()).or_default() += now - last;
+        }
+
+        self.start.get_or_insert(now);
+        self.last = Some(now);
+
+        now
+    }
+
+    fn function(&mut self, call: &Call) -> usize {
+        let key = Key {
+            name: call.name.to_string(),
+            definition: call
+                .function
+                .map(|function| (function.location.filename.clone(), function.location.start)),
+        };
+
+        *self.keys.entry(key).or_insert_with(|| {
+            self.functions.push(FunctionProfile {
+                name: call.name.to_string(),
+                location: call.function.map(|function| function.location.clone()),
+                calls: 0,
+                inclusive: Duration::ZERO,
+                exclusive: Duration::ZERO,
+                max_depth: 0,
+            });
+            self.active.push(0);
+            self.started.push(None);
+
+            self.functions.len() - 1
+        })
+    }
+
+    /// Stops measuring and returns the results. It should be called as soon
+    /// as evaluation finishes, as the time until then counts as the
+    /// program's.
+    pub fn finish(&mut self) -> Profile {
+        let now = self.tick();
+        let mut functions = self.functions.clone();
+
+        for (stack, time) in &self.stacks {
+            if let Some(index) = stack.last() {
+                functions[*index].exclusive += *time;
+            }
+        }
+
+        let mut stacks = self
+            .stacks
+            .iter()
+            .map(|(stack, time)| (stack.clone(), *time))
+            .collect::<Vec<_>>();
+
+        stacks.sort();
+
+        // Functions are sorted, so the stacks point to their new indices.
+        let mut order = (0..functions.len()).collect::<Vec<_>>();
+
+        order.sort_by(|a, b| {
+            functions[*b]
+                .exclusive
+                .cmp(&functions[*a].exclusive)
+                .then(functions[*b].inclusive.cmp(&functions[*a].inclusive))
+        });
+
+        let mut positions = vec![0; order.len()];
+
+        for (position, index) in order.iter().enumerate() {
+            positions[*index] = position;
+        }
+
+        for (stack, _) in &mut stacks {
+            for index in stack.iter_mut() {
+                *index = positions[*index];
+            }
+        }
+
+        Profile {
+            functions: order
+                .into_iter()
+                .map(|index| functions[index].clone())
+                .collect(),
+            total: self.start.map_or(Duration::ZERO, |start| now - start),
+            stacks,
+        }
+    }
+}
+
+impl Hook for Profiler {
+    fn enter_term(&mut self, _term: &ast::Term, _context: &Context) -> Result<(), Box<dyn Error>> {
+        if self.start.is_none() {
+            self.tick();
+        }
+
+        Ok(())
+    }
+
+    fn enter_call(&mut self, call: &Call) -> Result<(), Box<dyn Error>> {
+        let now = self.tick();
+        let index = self.function(call);
+
+        self.stack.push(index);
+        self.functions[index].calls += 1;
+        self.active[index] += 1;
+        self.functions[index].max_depth = self.functions[index].max_depth.max(self.active[index]);
+        self.started[index].get_or_insert(now);
+
+        Ok(())
+    }
+
+    fn exit_call(
+        &mut self,
+        _call: &Call,
+        _result: &Result<Value, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let now = self.tick();
+
+        if let Some(index) = self.stack.pop() {
+            self.active[index] -= 1;
+
+            if self.active[index] == 0 {
+                if let Some(started) = self.started[index].take() {
+                    self.functions[index].inclusive += now - started;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::ast::Location;
+
+/// A position in source code. Lines and columns start at 1, and columns count
+/// characters, not bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `location` as `file:line:column`, given the `position` it starts at, or as
+/// a byte range when its source code isn't loaded.
+pub fn describe_location(location: &Location, position: Option<Position>) -> String {
+    match position {
+        Some(position) => format!(
+            "{}:{}:{}",
+            location.filename, position.line, position.column
+        ),
+        None => format!("{}:{}..{}", location.filename, location.start, location.end),
+    }
+}
+
+/// The text of a source file, indexed by line.
+#[derive(Clone, Debug)]
+pub struct Source {
+    text: String,
+    lines: Vec<usize>,
+}
+
+impl Source {
+    pub fn new(text: String) -> Self {
+        let lines = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self { text, lines }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The byte range of `line`, without its line break, which may be `\n` or
+    /// `\r\n`.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.lines.get(line.checked_sub(1)?)?;
+        let end = self
+            .lines
+            .get(line)
+            .map_or(self.text.len(), |next| next - 1);
+        let end = if end > start && self.text.as_bytes()[end - 1] == b'\r' {
+            end - 1
+        } else {
+            end
+        };
+
+        Some(start..end)
+    }
+
+    pub fn line(&self, line: usize) -> Option<&str> {
+        self.line_range(line).map(|range| &self.text[range])
+    }
+
+    /// The text in `range`, unless it's out of bounds or splits a character.
+    pub fn snippet(&self, range: Range<usize>) -> Option<&str> {
+        self.text.get(range)
+    }
+
+    /// The position of the character at `offset`. Offsets past the end of
+    /// their line, or inside a character, are clamped to it.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.lines.partition_point(|start| *start <= offset);
+        let start = self.lines[line - 1];
+        let end = self.line_range(line).map_or(offset, |range| range.end);
+        let mut offset = offset.min(end);
+
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        Position {
+            line,
+            column: self.text[start..offset].chars().count() + 1,
+        }
+    }
+
+    pub fn offset(&self, position: Position) -> Option<usize> {
+        let range = self.line_range(position.line)?;
+        let line = &self.text[range.clone()];
+
+        line.char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(line.len()))
+            .nth(position.column.checked_sub(1)?)
+            .map(|index| range.start + index)
+    }
+}
+
+/// The source code of every loaded file, to show the positions of terms by
+/// line and column.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    files: HashMap<String, Source>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `text` as the source code of `filename`, replacing whatever was
+    /// loaded for it before.
+    pub fn add(&mut self, filename: impl Into<String>, text: String) -> &Source {
+        let filename = filename.into();
+
+        self.files.insert(filename.clone(), Source::new(text));
+
+        &self.files[&filename]
+    }
+
+    pub fn get(&self, filename: &str) -> Option<&Source> {
+        self.files.get(filename)
+    }
+
+    /// Where `location` starts.
+    pub fn position(&self, location: &Location) -> Option<Position> {
+        Some(self.get(&location.filename)?.position(location.start))
+    }
+
+    /// Where `location` starts and ends.
+    pub fn span(&self, location: &Location) -> Option<(Position, Position)> {
+        let source = self.get(&location.filename)?;
+
+        Some((
+            source.position(location.start),
+            source.position(location.end),
+        ))
+    }
+
+    pub fn offset(&self, filename: &str, position: Position) -> Option<usize> {
+        self.get(filename)?.offset(position)
+    }
+
+    pub fn snippet(&self, location: &Location) -> Option<&str> {
+        self.get(&location.filename)?
+            .snippet(location.start..location.end)
+    }
+
+    /// `location` as [`describe_location`] writes it.
+    pub fn describe(&self, location: &Location) -> String {
+        describe_location(location, self.position(location))
+    }
+}
@@ -0,0 +1,164 @@
+use std::{error::Error, io::Write};
+
+use crate::{
+    ast,
+    interpreter::{
+        hook::{Call, Hook},
+        Context, Value,
+    },
+    source::SourceMap,
+};
+
+const SNIPPET_WIDTH: usize = 40;
+
+#[derive(Clone, Debug, Default)]
+pub struct TraceOptions {
+    /// Only trace calls of this function, and everything they evaluate.
+    pub function: Option<String>,
+    /// How many nested calls to trace. Deeper ones are evaluated silently.
+    pub max_depth: Option<usize>,
+}
+
+fn kind(term: &ast::Term) -> &'static str {
+    match term {
+        ast::Term::Int(..) => "int",
+        ast::Term::Str(..) => "str",
+        ast::Term::Bool(..) => "bool",
+        ast::Term::Var(..) => "var",
+        ast::Term::Let(..) => "let",
+        ast::Term::If(..) => "if",
+        ast::Term::Call(..) => "call",
+        ast::Term::Binary(..) => "binary",
+        ast::Term::Function(..) => "function",
+        ast::Term::Print(..) => "print",
+        ast::Term::Tuple(..) => "tuple",
+        ast::Term::First(..) => "first",
+        ast::Term::Second(..) => "second",
+    }
+}
+
+/// A [`Hook`] that writes every step of evaluation to `output`, indented by
+/// how deep in calls it happens.
+pub struct Tracer<W> {
+    output: W,
+    sources: SourceMap,
+    options: TraceOptions,
+    /// Calls in progress.
+    depth: usize,
+    /// The depth of the outermost traced call, when tracing.
+    start: Option<usize>,
+}
+
+impl<W: Write> Tracer<W> {
+    /// Traces evaluation to `output`, showing terms as they are written in
+    /// `sources` when their source code is there.
+    pub fn new(output: W, sources: SourceMap, options: TraceOptions) -> Self {
+        let start = options.function.is_none().then_some(0);
+
+        Self {
+            output,
+            sources,
+            options,
+            depth: 0,
+            start,
+        }
+    }
+
+    fn snippet(&self, location: &ast::Location) -> String {
+        let Some(text) = self.sources.snippet(location) else {
+            return format!("{}..{}", location.start, location.end);
+        };
+
+        let line = text.lines().next().unwrap_or_default().trim_end();
+
+        if line.len() < text.len() || line.chars().count() > SNIPPET_WIDTH {
+            let line = line.chars().take(SNIPPET_WIDTH).collect::<String>();
+
+            format!("{line}…")
+        } else {
+            line.to_string()
+        }
+    }
+
+    fn line(&mut self, line: &str) -> Result<(), Box<dyn Error>> {
+        let Some(start) = self.start else {
+            return Ok(());
+        };
+
+        let level = self.depth - start;
+
+        if self
+            .options
+            .max_depth
+            .is_some_and(|max_depth| level > max_depth)
+        {
+            return Ok(());
+        }
+
+        writeln!(self.output, "{:indent$}{line}", "", indent = level * 2)?;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Hook for Tracer<W> {
+    fn enter_term(&mut self, term: &ast::Term, _context: &Context) -> Result<(), Box<dyn Error>> {
+        if self.start.is_some() {
+            let line = format!("{} {}", kind(term), self.snippet(term.location()));
+
+            self.line(&line)?;
+        }
+
+        Ok(())
+    }
+
+    fn enter_call(&mut self, call: &Call) -> Result<(), Box<dyn Error>> {
+        if self.start.is_none() && self.options.function.as_deref() == Some(call.name) {
+            self.start = Some(self.depth);
+        }
+
+        let parameters = call
+            .function
+            .map(|function| function.parameters.as_slice())
+            .unwrap_or_default();
+
+        let arguments = call
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(index, argument)| match parameters.get(index) {
+                Some(parameter) => format!("{} = {argument}", parameter.text),
+                None => argument.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.line(&format!("→ {}({arguments})", call.name))?;
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    fn exit_call(
+        &mut self,
+        call: &Call,
+        result: &Result<Value, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.depth -= 1;
+
+        match result {
+            Ok(value) => self.line(&format!("← {} = {value}", call.name))?,
+            Err(error) => self.line(&format!("← {} failed: {error}", call.name))?,
+        }
+
+        if self.options.function.is_some() && self.start == Some(self.depth) {
+            self.start = None;
+        }
+
+        Ok(())
+    }
+
+    fn bind(&mut self, name: &ast::Variable, value: &Value) -> Result<(), Box<dyn Error>> {
+        self.line(&format!("bind {} = {value}", name.text))
+    }
+}
@@ -0,0 +1,48 @@
+use caramuru::{ast::File, parse_source};
+
+const PROGRAMS: &[&str] = &[
+    "print(1 + 2 * 3 - 4 / 5 % 6)",
+    "let fib = fn (n) => if (n < 2) { n } else { fib(n - 1) + fib(n - 2) }; print(fib(10))",
+    "let t = (1, (\"a\", true)); print(first(second(t)))",
+    "let f = fn () => { let x = \"x\"; x + 1 }; f()",
+    "if (1 == 1 && 2 != 3 || 4 <= 5 && 6 >= 7 && 8 > 9) { false } else { true }",
+];
+
+fn reference_json(source: &str) -> String {
+    let file = rinha::parser::parse_or_report("test.rinha", source).unwrap();
+
+    serde_json::to_string(&file).unwrap()
+}
+
+#[test]
+fn serializes_like_the_reference_parser() {
+    for source in PROGRAMS {
+        let file = parse_source("test.rinha", source).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&file).unwrap(),
+            reference_json(source)
+        );
+    }
+}
+
+#[test]
+fn json_round_trips_through_the_ast() {
+    for source in PROGRAMS {
+        let json = reference_json(source);
+        let file: File = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(serde_json::to_string(&file).unwrap(), json);
+    }
+}
+
+#[test]
+fn let_names_are_plain_variables() {
+    let file = parse_source("test.rinha", "let x = 1; x").unwrap();
+    let json = serde_json::to_value(&file).unwrap();
+
+    assert_eq!(json["expression"]["kind"], "Let");
+    assert_eq!(json["expression"]["name"]["text"], "x");
+    assert!(json["expression"]["name"].get("kind").is_none());
+    assert_eq!(json["expression"]["next"]["kind"], "Var");
+}
@@ -0,0 +1,24 @@
+//! Helpers shared by the tests that run generated programs.
+
+use arbitrary::{Arbitrary, Unstructured};
+use caramuru::ast::generate::Program;
+
+// xorshift64*, enough to turn a seed into reproducible entropy.
+fn bytes(mut seed: u64, length: usize) -> Vec<u8> {
+    (0..length)
+        .map(|_| {
+            seed ^= seed >> 12;
+            seed ^= seed << 25;
+            seed ^= seed >> 27;
+
+            (seed.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 56) as u8
+        })
+        .collect()
+}
+
+/// The program generated from `seed`, the same on every run.
+pub fn program(seed: u64) -> Program {
+    let bytes = bytes(seed + 1, 4096);
+
+    Program::arbitrary(&mut Unstructured::new(&bytes)).unwrap()
+}
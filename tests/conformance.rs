@@ -0,0 +1,113 @@
+//! Runs every program in `tests/conformance`, comparing what it prints with
+//! the `.stdout` file next to it and the value it evaluates to with the
+//! `.value` file. Set `BLESS=1` to write the expected files instead.
+//!
+//! The programs in `tests/conformance/deviations` are where the interpreter
+//! knowingly departs from the language. Their expected files hold what the
+//! language specifies, which the interpreter must keep getting wrong until
+//! they are moved out. Blessing leaves them alone.
+
+use std::{
+    env,
+    fs::{read_dir, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+use caramuru::{
+    interpreter::{output::Buffer, Interpreter, Options},
+    parse_file,
+};
+
+fn programs(directory: &str) -> Vec<PathBuf> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join(directory);
+
+    let mut programs = read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "rinha" || extension == "json")
+        })
+        .collect::<Vec<_>>();
+
+    programs.sort();
+
+    programs
+}
+
+/// What `program` prints, and its value or the error it fails with.
+fn run(program: &Path) -> (String, String) {
+    // Errors name the program by the path it's read from, which is relative
+    // to the crate where tests run.
+    let path = program.strip_prefix(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+    let file = match parse_file(path.to_str().unwrap()) {
+        Ok(file) => file,
+        Err(error) => return (String::new(), format!("error: {error}\n")),
+    };
+    let output = Buffer::default();
+    let mut interpreter = Interpreter::new(Options::default(), output.clone());
+
+    let value = match interpreter.eval(&file) {
+        Ok(value) => format!("{value}\n"),
+        Err(error) => format!("error: {error}\n"),
+    };
+
+    (output.contents(), value)
+}
+
+#[test]
+fn conformance() {
+    let bless = env::var_os("BLESS").is_some_and(|bless| bless != "0");
+    let mut failures = Vec::new();
+
+    for program in programs("tests/conformance") {
+        let (stdout, value) = run(&program);
+
+        for (extension, actual) in [("stdout", stdout), ("value", value)] {
+            // Programs with the same name in both formats, like `fib.rinha`
+            // and `fib.json`, share their expected files.
+            let path = program.with_extension(extension);
+
+            if bless {
+                write(&path, &actual).unwrap();
+
+                continue;
+            }
+
+            match read_to_string(&path) {
+                Ok(expected) if expected == actual => {}
+                Ok(expected) => failures.push(format!(
+                    "{}: expected\n{expected}but got\n{actual}",
+                    path.display()
+                )),
+                Err(error) => failures.push(format!("{}: {error}", path.display())),
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\n\nrun with BLESS=1 to update the expected files",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn known_deviations() {
+    for program in programs("tests/conformance/deviations") {
+        let (stdout, value) = run(&program);
+
+        let expected = (
+            read_to_string(program.with_extension("stdout")).unwrap(),
+            read_to_string(program.with_extension("value")).unwrap(),
+        );
+
+        assert_ne!(
+            (stdout, value),
+            expected,
+            "{} now behaves as specified, move it out of the deviations",
+            program.display()
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use caramuru::{
+    coverage::{Coverage, Report},
+    interpreter::{eval_with_hook, output::Buffer, Options},
+    parse_source,
+    source::Source,
+};
+
+const PROGRAM: &str = "\
+let fib = fn (n) => {
+  if (n < 2) {
+    n
+  } else {
+    fib(n - 1) + fib(n - 2)
+  }
+};
+let unused = fn (x) => { x * 2 };
+let sign = fn (n) => { if (n < 0) { \"negative\" } else { \"positive\" } };
+print(sign(fib(5)))
+";
+
+fn report(options: Options) -> Report {
+    let file = parse_source("main.rinha", PROGRAM).unwrap();
+    let mut coverage = Coverage::new();
+
+    eval_with_hook(
+        file.clone(),
+        &options,
+        &mut Buffer::default(),
+        &mut coverage,
+    )
+    .unwrap();
+
+    coverage.report(&file)
+}
+
+#[test]
+fn counts_functions_branches_and_lets() {
+    let report = report(Options::default());
+
+    let functions = report
+        .functions
+        .iter()
+        .map(|function| (function.name.as_deref().unwrap(), function.calls))
+        .collect::<Vec<_>>();
+
+    assert_eq!(functions, [("fib", 15), ("unused", 0), ("sign", 1)]);
+
+    let branches = report
+        .branches
+        .iter()
+        .map(|branch| {
+            (
+                branch.condition.count,
+                branch.then.count,
+                branch.otherwise.count,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(branches, [(15, 8, 7), (1, 0, 1)]);
+    assert!(report.lets.iter().all(|term| term.count == 1));
+}
+
+#[test]
+fn writes_lcov() {
+    let report = report(Options::default());
+    let lcov = report.lcov("main.rinha", &Source::new(PROGRAM.to_string()));
+
+    assert!(lcov.starts_with("TN:\nSF:main.rinha\nFN:1,fib\nFN:8,unused\nFN:9,sign\n"));
+    assert!(lcov.contains("FNDA:0,unused\nFNDA:1,sign\nFNF:3\nFNH:2\n"));
+    assert!(lcov.contains("BRDA:2,0,0,8\nBRDA:2,0,1,7\nBRDA:9,1,0,0\nBRDA:9,1,1,1\nBRF:4\nBRH:3\n"));
+    assert!(lcov.contains("DA:2,15\nDA:3,8\nDA:5,7\n"));
+    assert!(lcov.ends_with("LF:7\nLH:7\nend_of_record\n"));
+}
+
+#[test]
+fn marks_unevaluated_code() {
+    let report = report(Options::default());
+    let listing = report.listing(&Source::new(PROGRAM.to_string()));
+
+    assert_eq!(
+        listing
+            .lines()
+            .filter(|line| line.contains('^'))
+            .collect::<Vec<_>>(),
+        [
+            "   |                          ^^^^^",
+            "   |                                     ^^^^^^^^^^",
+        ]
+    );
+    assert!(listing.ends_with("lets 3/3, functions 2/3, branches 3/4\n"));
+}
+
+#[test]
+fn covers_every_call_when_memoizing() {
+    let memoized = report(Options {
+        memoize: true,
+        ..Options::default()
+    });
+
+    assert_eq!(memoized.functions[0].calls, 15);
+}
@@ -0,0 +1,331 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+const FIB: &str = "\
+let fib = fn (n) => {
+  if (n < 2) {
+    n
+  } else {
+    fib(n - 1) + fib(n - 2)
+  }
+};
+print(fib(3))
+";
+
+fn program(name: &str, source: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("caramuru-dap-{}-{name}", std::process::id()));
+
+    fs::write(&path, source).unwrap();
+
+    path
+}
+
+/// A client that waits for what the adapter sends back before going on, like
+/// editors do, keeping every message it receives.
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: u64,
+    messages: Vec<Value>,
+    /// How many messages [`Client::wait_for`] already looked at.
+    seen: usize,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_caramuru"))
+            .arg("dap")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        Self {
+            stdin: child.stdin.take().unwrap(),
+            stdout: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            seq: 0,
+            messages: Vec::new(),
+            seen: 0,
+        }
+    }
+
+    /// Reads the next message, or `None` once the adapter exits.
+    fn receive(&mut self) -> Option<Value> {
+        let mut header = String::new();
+
+        if self.stdout.read_line(&mut header).unwrap() == 0 {
+            return None;
+        }
+
+        let length = header.trim_end()["Content-Length: ".len()..]
+            .parse::<usize>()
+            .unwrap();
+        let mut content = vec![0; length + 2];
+
+        self.stdout.read_exact(&mut content).unwrap();
+
+        let message: Value = serde_json::from_slice(&content[2..]).unwrap();
+
+        self.messages.push(message.clone());
+
+        Some(message)
+    }
+
+    /// Sends a request and waits for its response.
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+
+        let content = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+
+        write!(
+            self.stdin,
+            "Content-Length: {}\r\n\r\n{content}",
+            content.len()
+        )
+        .unwrap();
+
+        loop {
+            let message = self.receive().unwrap();
+
+            if message["type"] == "response" && message["request_seq"] == self.seq {
+                return message;
+            }
+        }
+    }
+
+    /// Waits for the next `event` after the last one waited for.
+    fn wait_for(&mut self, event: &str) -> Value {
+        loop {
+            if let Some(index) = self.messages[self.seen..]
+                .iter()
+                .position(|message| message["event"] == event)
+            {
+                self.seen += index + 1;
+
+                return self.messages[self.seen - 1]["body"].clone();
+            }
+
+            self.receive().unwrap();
+        }
+    }
+
+    /// Waits for the adapter to exit, returning everything it sent.
+    fn finish(mut self) -> Vec<Value> {
+        while self.receive().is_some() {}
+
+        assert!(self.child.wait().unwrap().success());
+
+        self.messages
+    }
+}
+
+fn response(messages: &[Value], request_seq: u64) -> &Value {
+    messages
+        .iter()
+        .find(|message| message["type"] == "response" && message["request_seq"] == request_seq)
+        .unwrap()
+}
+
+fn events<'a>(messages: &'a [Value], event: &'a str) -> impl Iterator<Item = &'a Value> {
+    messages
+        .iter()
+        .filter(move |message| message["event"] == event)
+        .map(|message| &message["body"])
+}
+
+#[test]
+fn stops_at_breakpoints_and_shows_the_stack() {
+    let path = program("fib.rinha", FIB);
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "adapterID": "rinha" }));
+    client.request("launch", json!({ "program": path }));
+    client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [{ "line": 3 }, { "line": 99 }] }),
+    );
+    client.request("configurationDone", json!({}));
+    client.wait_for("stopped");
+    client.request("stackTrace", json!({ "threadId": 1 }));
+    client.request("scopes", json!({ "frameId": 1 }));
+    client.request("variables", json!({ "variablesReference": 1 }));
+    client.request("evaluate", json!({ "expression": "n", "frameId": 0 }));
+    client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [] }),
+    );
+    client.request("continue", json!({ "threadId": 1 }));
+    client.wait_for("terminated");
+    client.request("disconnect", json!({}));
+
+    let messages = client.finish();
+
+    fs::remove_file(path).unwrap();
+
+    let breakpoints = &response(&messages, 3)["body"]["breakpoints"];
+
+    assert_eq!(breakpoints[0]["verified"], true);
+    assert_eq!(breakpoints[1]["verified"], false);
+
+    let stops = events(&messages, "stopped").collect::<Vec<_>>();
+
+    assert_eq!(stops.len(), 1);
+    assert_eq!(stops[0]["reason"], "breakpoint");
+    assert_eq!(stops[0]["hitBreakpointIds"], json!([1]));
+
+    let frames = response(&messages, 5)["body"]["stackFrames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|frame| {
+            (
+                frame["name"].as_str().unwrap(),
+                frame["line"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(frames, [("fib", 3), ("fib", 5), ("fib", 5), ("<main>", 8)]);
+
+    let variables = &response(&messages, 7)["body"]["variables"];
+
+    assert_eq!(variables[0]["name"], "n");
+    assert_eq!(variables[0]["value"], "2");
+    assert_eq!(response(&messages, 8)["body"]["result"], "1");
+
+    let output = events(&messages, "output")
+        .filter(|body| body["category"] == "stdout")
+        .map(|body| body["output"].as_str().unwrap())
+        .collect::<String>();
+
+    assert_eq!(output, "2\n");
+    assert_eq!(events(&messages, "exited").next().unwrap()["exitCode"], 0);
+    assert_eq!(response(&messages, 11)["success"], true);
+}
+
+#[test]
+fn steps_from_the_entry_and_expands_tuples() {
+    let path = program(
+        "tuple.rinha",
+        "let t = (1, \"a\");\nlet x = first(t);\nprint(x + true)\n",
+    );
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "adapterID": "rinha" }));
+    client.request("launch", json!({ "program": path, "stopOnEntry": true }));
+    client.request("configurationDone", json!({}));
+    client.wait_for("stopped");
+    client.request("next", json!({ "threadId": 1 }));
+    client.wait_for("stopped");
+    client.request("next", json!({ "threadId": 1 }));
+    client.wait_for("stopped");
+    client.request("scopes", json!({ "frameId": 0 }));
+    client.request("variables", json!({ "variablesReference": 1 }));
+    client.request("variables", json!({ "variablesReference": 2 }));
+    client.request("continue", json!({ "threadId": 1 }));
+    client.wait_for("terminated");
+    client.request("disconnect", json!({}));
+
+    let messages = client.finish();
+
+    fs::remove_file(path).unwrap();
+
+    let reasons = events(&messages, "stopped")
+        .map(|body| body["reason"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(reasons, ["entry", "step", "step"]);
+
+    let variables = &response(&messages, 7)["body"]["variables"];
+
+    assert_eq!(variables[0]["name"], "t");
+    assert_eq!(variables[0]["type"], "tuple");
+    assert_eq!(
+        response(&messages, 8)["body"]["variables"],
+        json!([
+            { "name": "first", "value": "1", "type": "integer", "variablesReference": 0 },
+            { "name": "second", "value": "a", "type": "string", "variablesReference": 0 },
+        ])
+    );
+
+    let errors = events(&messages, "output")
+        .filter(|body| body["category"] == "stderr")
+        .count();
+
+    assert_eq!(errors, 1);
+    assert_eq!(events(&messages, "exited").next().unwrap()["exitCode"], 1);
+    assert!(events(&messages, "terminated").next().is_some());
+}
+
+const LONG: &str = "\
+let fib = fn (n) => {
+  if (n < 2) {
+    n
+  } else {
+    fib(n - 1) + fib(n - 2)
+  }
+};
+print(fib(40))
+";
+
+#[test]
+fn pauses_a_running_program() {
+    let path = program("long.rinha", LONG);
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "adapterID": "rinha" }));
+    client.request("launch", json!({ "program": path }));
+    client.request("configurationDone", json!({}));
+
+    assert_eq!(
+        client.request("pause", json!({ "threadId": 1 }))["success"],
+        true
+    );
+    assert_eq!(client.wait_for("stopped")["reason"], "pause");
+
+    // Wherever it paused, the program has a stack to show.
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+
+    assert!(!trace["body"]["stackFrames"].as_array().unwrap().is_empty());
+
+    client.request("disconnect", json!({}));
+
+    let messages = client.finish();
+
+    fs::remove_file(path).unwrap();
+
+    assert!(events(&messages, "exited").next().is_none());
+}
+
+#[test]
+fn disconnects_from_a_running_program() {
+    let path = program("endless.rinha", LONG);
+    let mut client = Client::start();
+
+    client.request("initialize", json!({ "adapterID": "rinha" }));
+    client.request("launch", json!({ "program": path }));
+    client.request("configurationDone", json!({}));
+
+    assert_eq!(client.request("disconnect", json!({}))["success"], true);
+
+    let messages = client.finish();
+
+    fs::remove_file(path).unwrap();
+
+    assert!(events(&messages, "stopped").next().is_none());
+    assert!(events(&messages, "exited").next().is_none());
+}
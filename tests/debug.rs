@@ -0,0 +1,120 @@
+use std::{cell::RefCell, io, rc::Rc};
+
+use caramuru::{
+    debug::{Debugger, Prompt, Session},
+    interpreter::{output::Buffer, Interpreter, Options},
+    parse_source,
+    source::{Position, Source},
+};
+
+const FIB: &str = "\
+let fib = fn (n) => {
+  if (n < 2) {
+    n
+  } else {
+    fib(n - 1) + fib(n - 2)
+  }
+};
+print(fib(3))
+";
+
+#[derive(Clone, Default)]
+struct Transcript(Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for Transcript {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn debug(source: &str, commands: &str) -> (Result<String, String>, String) {
+    let file = parse_source("fib.rinha", source).unwrap();
+    let output = Buffer::default();
+    let transcript = Transcript::default();
+    let mut interpreter = Interpreter::new(Options::default(), output.clone());
+
+    interpreter.set_hook(Debugger::new(
+        Session::new(Some(Source::new(source.to_string()))),
+        Prompt::new(io::Cursor::new(commands.to_string()), transcript.clone()),
+    ));
+
+    let result = interpreter
+        .eval(&file)
+        .map(|_| output.contents())
+        .map_err(|error| error.to_string());
+    let transcript = String::from_utf8(transcript.0.take()).unwrap();
+
+    (result, transcript)
+}
+
+#[test]
+fn stops_at_line_breakpoints() {
+    let (result, transcript) = debug(FIB, "b 3\nc\nbt\np n\nc\nd 1\nc\n");
+
+    assert_eq!(result.unwrap(), "2\n");
+    assert!(transcript.contains("breakpoint 1 at line 3"));
+    assert!(transcript.contains("breakpoint 1 at fib.rinha:3:5\n3 |     n\n  |     ^\n"));
+    assert!(transcript.contains(
+        "#0 fib(n = 1) at fib.rinha:3:5\n\
+         #1 fib(n = 2) at fib.rinha:5:5\n\
+         #2 fib(n = 3) at fib.rinha:5:5\n\
+         #3 <main> at fib.rinha:8:7\n"
+    ));
+    assert!(transcript.contains("n = 1\n"));
+    assert_eq!(transcript.matches("breakpoint 1 at fib").count(), 2);
+}
+
+#[test]
+fn steps_over_and_out_of_calls() {
+    let (result, transcript) = debug(FIB, "b 5\nc\nd 1\nn\nn\nn\nbt\no\n");
+
+    let stops = transcript
+        .lines()
+        .map(|line| line.trim_start_matches("(rinha) "))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.rsplit("fib.rinha:").next())
+        .filter(|position| position.contains(':') && !position.contains(' '))
+        .collect::<Vec<_>>();
+
+    // The entry, the breakpoint, two steps within the line, then the call of
+    // `fib(n - 2)` without stopping inside `fib(n - 1)`. Stepping out of the
+    // outermost call runs to the end.
+    assert_eq!(stops, ["1:1", "5:5", "5:5", "5:9", "5:18"]);
+    assert!(transcript.contains("#0 fib(n = 3) at fib.rinha:5:18\n#1 <main> at fib.rinha:8:7\n"));
+    assert_eq!(result.unwrap(), "2\n");
+}
+
+#[test]
+fn quitting_stops_evaluation() {
+    let (result, transcript) = debug(FIB, "l\nq\n");
+
+    assert_eq!(result.unwrap_err(), "stopped by the debugger");
+    assert!(transcript.starts_with("stopped at fib.rinha:1:1\n"));
+}
+
+#[test]
+fn source_positions_count_characters() {
+    let source = Source::new("let a = \"ção\";\r\nprint(a)".to_string());
+
+    assert_eq!(source.position(0), Position { line: 1, column: 1 });
+    assert_eq!(
+        source.position(14),
+        Position {
+            line: 1,
+            column: 13
+        }
+    );
+    assert_eq!(source.position(18), Position { line: 2, column: 1 });
+    assert_eq!(
+        source.offset(Position {
+            line: 1,
+            column: 13
+        }),
+        Some(14)
+    );
+    assert_eq!(source.offset(Position { line: 3, column: 1 }), None);
+}
@@ -0,0 +1,182 @@
+//! Runs random programs with the interpreter alone and after the optimizer,
+//! which must agree on what they print and how they fail. Failing programs
+//! are shrunk before being reported.
+//!
+//! The fuzz test runs until `FUZZ_SECONDS` pass, from `FUZZ_SEED` or a seed
+//! taken from the clock:
+//!
+//! ```sh
+//! FUZZ_SECONDS=600 cargo test --release --test differential -- --ignored
+//! ```
+
+mod common;
+
+use std::{
+    env,
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::{Duration, Instant, SystemTime},
+};
+
+use caramuru::{
+    ast::{build as b, generate::Program, shrink::shrink, Term},
+    fmt::{format_file, DEFAULT_WIDTH},
+    interpreter::{eval, output::Buffer, LimitExceeded, Options},
+    opt::optimize,
+    parse_source,
+};
+
+use self::common::program;
+
+const PROGRAMS: u64 = 300;
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Finished {
+        output: String,
+        error: Option<String>,
+    },
+    Panicked {
+        output: String,
+        message: String,
+    },
+    /// Hit a limit. The optimizer changes how many steps and calls a program
+    /// takes, so limits are no grounds for comparison.
+    Inconclusive,
+}
+
+fn run(term: &Term, optimized: bool) -> Outcome {
+    let file = b::file(term.clone());
+    let file = if optimized { optimize(file) } else { file };
+    let output = Buffer::default();
+
+    // Shrinking may remove what made recursion stop.
+    let options = Options {
+        max_steps: Some(1_000_000),
+        max_depth: Some(200),
+        ..Options::default()
+    };
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        eval(file, &options, &mut output.clone())
+    }));
+
+    match result {
+        Ok(Err(error)) if error.is::<LimitExceeded>() => Outcome::Inconclusive,
+        Ok(result) => Outcome::Finished {
+            output: output.contents(),
+            error: result.err().map(|error| error.to_string()),
+        },
+        Err(panic) => Outcome::Panicked {
+            output: output.contents(),
+            message: panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default(),
+        },
+    }
+}
+
+fn disagree(term: &Term) -> bool {
+    let interpreted = run(term, false);
+    let optimized = run(term, true);
+
+    interpreted != Outcome::Inconclusive
+        && optimized != Outcome::Inconclusive
+        && interpreted != optimized
+}
+
+/// The smallest version of the program generated from `seed` on which the
+/// interpreter and the optimizer disagree, if they do.
+fn check(seed: u64) -> Result<(), String> {
+    let Program(file) = program(seed);
+
+    if !disagree(&file.expression) {
+        return Ok(());
+    }
+
+    let term = shrink(file.expression, disagree);
+
+    Err(format!(
+        "seed {seed}:\n{}\ninterpreted: {:?}\noptimized: {:?}",
+        format_file(&b::file(term.clone()), DEFAULT_WIDTH),
+        run(&term, false),
+        run(&term, true)
+    ))
+}
+
+#[test]
+fn optimizer_agrees_with_interpreter() {
+    for seed in 0..PROGRAMS {
+        if let Err(failure) = check(seed) {
+            panic!("{failure}");
+        }
+    }
+}
+
+#[test]
+#[ignore = "runs for FUZZ_SECONDS, 60 by default"]
+fn fuzz_optimizer_against_interpreter() {
+    let seconds = env::var("FUZZ_SECONDS").map_or(60, |seconds| seconds.parse().unwrap());
+    let seed = env::var("FUZZ_SEED").map_or_else(
+        |_| {
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        },
+        |seed| seed.parse().unwrap(),
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(seconds);
+
+    println!("fuzzing from seed {seed}");
+
+    for seed in seed.. {
+        if Instant::now() > deadline {
+            break;
+        }
+
+        if let Err(failure) = check(seed) {
+            panic!("{failure}");
+        }
+    }
+}
+
+#[test]
+fn optimizer_keeps_calls_that_fail() {
+    for source in [
+        "print((fn (x) => { x })(1))",
+        "let h = let f = fn (x) => { x }; fn () => { f(1) }; print(h())",
+    ] {
+        let term = parse_source("test.rinha", source).unwrap().expression;
+
+        assert!(matches!(
+            run(&term, false),
+            Outcome::Finished { error: Some(_), .. }
+        ));
+        assert_eq!(run(&term, true), run(&term, false), "{source}");
+    }
+}
+
+#[test]
+fn shrinks_to_the_failing_part() {
+    let term = b::let_(
+        "x",
+        b::add(b::int(40), b::int(2)),
+        b::tuple(b::print(b::str("kept")), b::print(b::var("x"))),
+    );
+
+    // A program fails when it prints something with "e".
+    let prints_e = |term: &Term| match run(term, false) {
+        Outcome::Finished { output, .. } => output.contains('e'),
+        _ => false,
+    };
+
+    let shrunk = shrink(term, prints_e);
+
+    assert_eq!(
+        format_file(&b::file(shrunk), DEFAULT_WIDTH),
+        "print(\"kept\")\n"
+    );
+}
@@ -0,0 +1,85 @@
+use caramuru::{
+    fmt::{format_file, has_comments, DEFAULT_WIDTH},
+    parse_source,
+};
+use serde_json::Value;
+
+const PROGRAMS: &[&str] = &[
+    "print(1 + 2 * 3 - 4 / 5 % 6)",
+    "print((1 - 2) - 3 - (4 * 5) * 6 / (7 % 8))",
+    "print((1 == 2) == (3 < 4) || (true && false))",
+    "let fib = fn (n) => if (n < 2) { n } else { fib(n - 1) + fib(n - 2) }; print(fib(10))",
+    "let t = (1, (\"a\\\"b\", true)); print(first(second(t)))",
+    "let f = fn () => { let x = \"x\"; x + 1 }; f()",
+    "let x = let y = 1; y + 2; print((fn (a, b) => (b, a))(x, if (x > 2) { x } else { 0 }))",
+    "let g = fn () => fn (x) => x * 2; let h = g(); h(print((if (true) { 1 } else { 2 }) + 3))",
+    "let long = aaaaaaaaaaaaaaaaaaaa + bbbbbbbbbbbbbbbbbbbb * cccccccccccccccccccc - dddddddddddddddddddd + eeeeeeeeeeeeeeeeeeee; long",
+];
+
+fn format(source: &str, width: usize) -> String {
+    format_file(&parse_source("test.rinha", source).unwrap(), width)
+}
+
+fn without_locations(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.remove("location");
+            object.values_mut().for_each(without_locations);
+        }
+        Value::Array(array) => array.iter_mut().for_each(without_locations),
+        _ => {}
+    }
+}
+
+fn shape(source: &str) -> Value {
+    let mut value = serde_json::to_value(parse_source("test.rinha", source).unwrap()).unwrap();
+
+    without_locations(&mut value);
+
+    value
+}
+
+#[test]
+fn formatting_preserves_the_ast() {
+    for source in PROGRAMS {
+        for width in [DEFAULT_WIDTH, 20, 1] {
+            assert_eq!(shape(&format(source, width)), shape(source), "{source}");
+        }
+    }
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    for source in PROGRAMS {
+        for width in [DEFAULT_WIDTH, 20, 1] {
+            let formatted = format(source, width);
+
+            assert_eq!(format(&formatted, width), formatted, "{source}");
+        }
+    }
+}
+
+#[test]
+fn only_needed_parentheses_are_kept() {
+    assert_eq!(
+        format("print(((1 - 2)) - (3 - (4 * 5)))", DEFAULT_WIDTH),
+        "print((1 - 2) - 3 - 4 * 5)\n"
+    );
+}
+
+#[test]
+fn long_lines_are_broken() {
+    let formatted = format(PROGRAMS[PROGRAMS.len() - 1], DEFAULT_WIDTH);
+
+    assert!(formatted.lines().all(|line| line.len() <= DEFAULT_WIDTH));
+    assert_eq!(formatted.lines().count(), 6);
+}
+
+#[test]
+fn comments_are_detected_outside_strings() {
+    assert!(has_comments("// comment\n1"));
+    assert!(has_comments("1 /* comment */"));
+    assert!(!has_comments("print(\"// not a comment\")"));
+    assert!(!has_comments("print(\"\\\" /* not a comment\")"));
+    assert!(!has_comments("print(4 / 2)"));
+}
@@ -0,0 +1,74 @@
+use caramuru::{
+    ast::File,
+    fmt::{format_file, DEFAULT_WIDTH},
+    opt::fold_constants,
+    parse_source,
+};
+
+fn fold(source: &str) -> String {
+    let file = parse_source("test.rinha", source).unwrap();
+    let expression = fold_constants(file.expression);
+
+    format_file(&File { expression, ..file }, DEFAULT_WIDTH)
+}
+
+#[test]
+fn folds_operations_on_literals() {
+    assert_eq!(fold("print(1 + 2 * 3)"), "print(7)\n");
+    assert_eq!(fold("print(\"a\" + (1 + 2))"), "print(\"a3\")\n");
+    assert_eq!(fold("print((1 < 2) && (3 == 3))"), "print(true)\n");
+    assert_eq!(fold("print(0 - 5)"), "print((0 - 5))\n");
+}
+
+#[test]
+fn folds_conditions_and_tuples() {
+    assert_eq!(
+        fold("if (1 < 2) { print(1) } else { print(2) }"),
+        "print(1)\n"
+    );
+    assert_eq!(fold("print(second((1, \"b\")))"), "print(\"b\")\n");
+}
+
+#[test]
+fn simplifies_identities() {
+    assert_eq!(
+        fold("print(fn (n) => { (n * 2) * 1 })"),
+        "print(fn (n) => { n * 2 })\n"
+    );
+    assert_eq!(
+        fold("print(fn (n) => { true && n < 1 })"),
+        "print(fn (n) => { n < 1 })\n"
+    );
+    // `n` may not be an integer, and `n * 1` fails when it isn't.
+    assert_eq!(
+        fold("print(fn (n) => { n * 1 })"),
+        "print(fn (n) => { n * 1 })\n"
+    );
+}
+
+#[test]
+fn propagates_constant_bindings() {
+    assert_eq!(fold("let x = 2; print(x * 3)"), "print(6)\n");
+}
+
+#[test]
+fn keeps_bindings_calls_could_see() {
+    // `f` reads `x` from the bindings at its call site.
+    let source = "let x = 2;\nlet f = fn () => { x };\nprint(f() + x)\n";
+
+    assert_eq!(fold(source), source.replace("+ x", "+ 2"));
+}
+
+#[test]
+fn leaves_failing_and_overflowing_operations_unfolded() {
+    for source in [
+        "print(1 / 0)\n",
+        "print(1 % 0)\n",
+        "print(1 + true)\n",
+        "print(2147483647 + 1)\n",
+        "print((0 - 2147483647) - 2)\n",
+        "print(65536 * 65536)\n",
+    ] {
+        assert_eq!(fold(source), source);
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use caramuru::{
+    ast::{build as b, generate::Program},
+    fmt::{format_file, DEFAULT_WIDTH},
+    interpreter::{output::Buffer, Interpreter, Options, Value},
+    parse_source,
+};
+
+use self::common::program;
+
+const PROGRAMS: u64 = 200;
+
+#[test]
+fn builder_fills_in_locations() {
+    let file = b::file(b::let_(
+        "x",
+        b::int(1),
+        b::add(b::var("x"), b::mul(b::int(2), b::int(3))),
+    ));
+
+    assert_eq!(format_file(&file, DEFAULT_WIDTH), "let x = 1;\nx + 2 * 3\n");
+
+    let value = Interpreter::new(Options::default(), Buffer::default())
+        .eval(&file)
+        .unwrap();
+
+    assert_eq!(i32::try_from(value).unwrap(), 7);
+}
+
+#[test]
+fn generated_programs_are_well_scoped() {
+    for seed in 0..PROGRAMS {
+        let Program(file) = program(seed);
+        let source = format_file(&file, DEFAULT_WIDTH);
+
+        let result = Interpreter::new(Options::default(), Buffer::default())
+            .eval(&file)
+            .map_err(|error| error.to_string());
+
+        assert!(
+            matches!(result, Ok(ref value) if !matches!(value, Value::Function(..))),
+            "{source}{result:?}"
+        );
+    }
+}
+
+#[test]
+fn generated_programs_survive_formatting() {
+    for seed in 0..PROGRAMS {
+        let Program(file) = program(seed);
+        let source = format_file(&file, DEFAULT_WIDTH);
+        let parsed = parse_source("test.rinha", &source).unwrap();
+
+        assert_eq!(format_file(&parsed, DEFAULT_WIDTH), source);
+    }
+}
@@ -0,0 +1,68 @@
+use caramuru::{
+    ast::File,
+    fmt::{format_file, DEFAULT_WIDTH},
+    opt::inline_functions,
+    parse_source,
+};
+
+fn inline(source: &str) -> String {
+    let file = parse_source("test.rinha", source).unwrap();
+    let expression = inline_functions(file.expression);
+
+    format_file(&File { expression, ..file }, DEFAULT_WIDTH)
+}
+
+fn unchanged(source: &str) {
+    let formatted = format_file(&parse_source("test.rinha", source).unwrap(), DEFAULT_WIDTH);
+
+    assert_eq!(inline(source), formatted);
+}
+
+#[test]
+fn beta_reduces_calls_to_small_functions() {
+    assert_eq!(
+        inline("let add = fn (a, b) => { a + b }; print(add(1, 2))"),
+        "let add = fn (a, b) => { a + b };\nprint(\n  {\n    let a = 1;\n    let b = 2;\n    a + b\n  }\n)\n"
+    );
+}
+
+#[test]
+fn evaluates_arguments_before_binding_parameters() {
+    // `b` must be bound to the caller's `a`, not to the parameter.
+    assert_eq!(
+        inline("let f = fn (a, b) => { a - b }; let a = 5; f(1, a)"),
+        "let f = fn (a, b) => { a - b };\nlet a = 5;\nlet a_0 = 1;\nlet b_1 = a;\nlet a = a_0;\nlet b = b_1;\na - b\n"
+    );
+}
+
+#[test]
+fn inlines_into_functions_called_in_scope() {
+    // The body reads `x` from its call site, inlined or not.
+    assert_eq!(
+        inline("let get = fn () => { x }; let g = fn (x) => { get() }; g(2)"),
+        "let get = fn () => { x };\nlet g = fn (x) => { x };\nlet x = 2;\nx\n"
+    );
+}
+
+#[test]
+fn leaves_recursive_functions() {
+    unchanged("let f = fn (n) => { if (n == 0) { 0 } else { f(n - 1) } }; f(3)");
+}
+
+#[test]
+fn leaves_names_bound_more_than_once() {
+    // The parameter `f` shadows the function of the same name.
+    assert_eq!(
+        inline("let f = fn (n) => { n }; let g = fn (f) => { f(1) }; g(f)"),
+        "let f = fn (n) => { n };\nlet g = fn (f) => { f(1) };\nlet f = f;\nf(1)\n"
+    );
+}
+
+#[test]
+fn leaves_calls_that_fail_at_runtime() {
+    // Only names can be called.
+    unchanged("print((fn (x) => { x })(1))");
+
+    // `h` is called after the `let` binding `f` is done.
+    unchanged("let h = let f = fn (x) => { x }; fn () => { f(1) }; print(h())");
+}
@@ -0,0 +1,103 @@
+use caramuru::{
+    interpreter::{output::Buffer, Interpreter, Options, Value},
+    parse_source,
+};
+
+fn interpreter() -> (Interpreter, Buffer) {
+    let output = Buffer::default();
+    let mut interpreter = Interpreter::new(Options::default(), output.clone());
+
+    interpreter.register("add", |arguments| match arguments {
+        [Value::Integer(lhs), Value::Integer(rhs)] => Ok(Value::Integer(lhs + rhs)),
+        _ => Err(format!("add expects 2 integers, but got {} values", arguments.len()).into()),
+    });
+
+    (interpreter, output)
+}
+
+fn eval(interpreter: &mut Interpreter, source: &str) -> Result<Value, String> {
+    interpreter
+        .eval(&parse_source("test.rinha", source).unwrap())
+        .map_err(|error| error.to_string())
+}
+
+#[test]
+fn programs_see_defined_globals() {
+    let (mut interpreter, output) = interpreter();
+
+    interpreter.define("answer", Value::Integer(42));
+    interpreter.define("pair", ("rinha", true).into());
+
+    eval(&mut interpreter, "let _ = print(answer);\nprint(pair)").unwrap();
+
+    assert_eq!(output.contents(), "42\n(rinha, true)\n");
+}
+
+#[test]
+fn globals_outlive_each_evaluation() {
+    let (mut interpreter, _) = interpreter();
+
+    interpreter.define("answer", Value::Integer(42));
+
+    for _ in 0..2 {
+        let value = eval(&mut interpreter, "answer + 1").unwrap();
+
+        assert!(matches!(value, Value::Integer(43)));
+    }
+}
+
+#[test]
+fn bindings_shadow_globals() {
+    let (mut interpreter, _) = interpreter();
+
+    interpreter.define("answer", Value::Integer(42));
+
+    let value = eval(&mut interpreter, "let answer = 1;\nanswer").unwrap();
+
+    assert!(matches!(value, Value::Integer(1)));
+}
+
+#[test]
+fn natives_are_called_like_functions() {
+    let (mut interpreter, output) = interpreter();
+
+    eval(
+        &mut interpreter,
+        "let twice = fn (f, x) => { f(x, x) };\nprint(twice(add, add(1, 2)))",
+    )
+    .unwrap();
+
+    assert_eq!(output.contents(), "6\n");
+}
+
+#[test]
+fn natives_check_their_own_arity() {
+    let (mut interpreter, _) = interpreter();
+
+    assert_eq!(
+        eval(&mut interpreter, "add(1)").unwrap_err(),
+        "add expects 2 integers, but got 1 values"
+    );
+}
+
+#[test]
+fn functions_need_every_argument() {
+    let (mut interpreter, _) = interpreter();
+
+    assert_eq!(
+        eval(&mut interpreter, "let f = fn (a, b) => { a };\nf(1)").unwrap_err(),
+        "'f' expected 2 arguments, but got 1"
+    );
+}
+
+#[test]
+fn globals_that_are_not_functions_cant_be_called() {
+    let (mut interpreter, _) = interpreter();
+
+    interpreter.define("answer", Value::Integer(42));
+
+    assert_eq!(
+        eval(&mut interpreter, "answer(1)").unwrap_err(),
+        "Function callee is not valid"
+    );
+}
@@ -0,0 +1,157 @@
+use std::{env, error::Error, fs, process::Command, thread, time::Instant};
+
+use caramuru::{
+    interpreter::{
+        eval, eval_with_hook, hook::Call, output::Buffer, Hook, Limit, LimitExceeded, Options,
+        Value, DEFAULT_MAX_DEPTH,
+    },
+    parse::{parse, SyntaxError, MAX_NESTING},
+    parse_source,
+};
+
+const STACK_SIZE: usize = 64 << 20;
+
+const RECURSION: &str = "let f = fn (n) => { f(n + 1) };\nf(0)";
+
+fn exceeded(source: &str, options: Options) -> (Limit, &str) {
+    let file = parse_source("main.rinha", source).unwrap();
+    let error = eval(file, &options, &mut Buffer::default()).unwrap_err();
+    let exceeded = error.downcast_ref::<LimitExceeded>().unwrap();
+
+    assert_eq!(exceeded.location.filename, "main.rinha");
+
+    (
+        exceeded.limit,
+        &source[exceeded.location.start..exceeded.location.end],
+    )
+}
+
+#[test]
+fn stops_after_max_steps() {
+    let options = Options {
+        max_steps: Some(3),
+        ..Options::default()
+    };
+
+    // The `let`, the function and the call fit, but not the argument.
+    assert_eq!(exceeded(RECURSION, options), (Limit::Steps(3), "0"));
+}
+
+#[test]
+fn stops_at_max_depth() {
+    let options = Options {
+        max_depth: Some(3),
+        ..Options::default()
+    };
+
+    assert_eq!(exceeded(RECURSION, options), (Limit::Depth(3), "f(n + 1)"));
+}
+
+#[test]
+fn stops_unbounded_recursion_by_default() {
+    assert_eq!(
+        exceeded(RECURSION, Options::default()),
+        (Limit::Depth(DEFAULT_MAX_DEPTH), "f(n + 1)")
+    );
+}
+
+#[test]
+fn command_line_stops_unbounded_recursion() {
+    let path = env::temp_dir().join(format!("caramuru-limits-{}.rinha", std::process::id()));
+
+    fs::write(&path, RECURSION).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_caramuru"))
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("exceeded the maximum call depth"));
+}
+
+#[test]
+fn rejects_terms_nested_too_deep() {
+    let source = format!("{}1", "1 + ".repeat(MAX_NESTING));
+    let error = parse("main.rinha", &source).unwrap_err();
+
+    assert!(matches!(
+        error.errors(),
+        [SyntaxError::TooDeep { span }] if span.offset() == source.len() - 1
+    ));
+}
+
+#[test]
+fn runs_long_sequences_of_bindings() {
+    let source = (0..1200)
+        .map(|i| format!("let x{i} = {i};\n"))
+        .chain(["print(x1199)".to_string()])
+        .collect::<String>();
+
+    // Converting and dropping the AST recurse through every `let`, which
+    // takes more than the stack of a test thread.
+    let output = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let file = parse("main.rinha", &source).unwrap();
+            let output = Buffer::default();
+
+            eval(file, &Options::default(), &mut output.clone()).unwrap();
+
+            output.contents()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(output, "1199\n");
+}
+
+#[test]
+fn stops_at_the_deadline() {
+    let options = Options {
+        deadline: Some(Instant::now()),
+        ..Options::default()
+    };
+
+    assert_eq!(exceeded(RECURSION, options), (Limit::Deadline, RECURSION));
+}
+
+#[derive(Default)]
+struct Calls {
+    entered: usize,
+    exited: usize,
+}
+
+impl Hook for Calls {
+    fn enter_call(&mut self, _call: &Call) -> Result<(), Box<dyn Error>> {
+        self.entered += 1;
+
+        Ok(())
+    }
+
+    fn exit_call(
+        &mut self,
+        _call: &Call,
+        _result: &Result<Value, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.exited += 1;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn hooks_exit_every_call_they_enter() {
+    let file = parse_source("main.rinha", RECURSION).unwrap();
+    let options = Options {
+        max_depth: Some(3),
+        ..Options::default()
+    };
+    let mut calls = Calls::default();
+
+    assert!(eval_with_hook(file, &options, &mut Buffer::default(), &mut calls).is_err());
+    assert_eq!((calls.entered, calls.exited), (3, 3));
+}
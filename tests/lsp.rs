@@ -0,0 +1,241 @@
+use std::{
+    env,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+const URI: &str = "file:///tmp/main.rinha";
+
+enum Message {
+    Request(&'static str, Value),
+    Notification(&'static str, Value),
+}
+
+use Message::*;
+
+fn open(text: &str) -> Message {
+    Notification(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": { "uri": URI, "languageId": "rinha", "version": 1, "text": text },
+        }),
+    )
+}
+
+fn at(method: &'static str, line: u64, character: u64) -> Message {
+    Request(
+        method,
+        json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        }),
+    )
+}
+
+/// Runs a whole session, from initialization to exit, and returns the
+/// results of the requests in order and the diagnostics published.
+fn session(messages: Vec<Message>) -> (Vec<Value>, Vec<Value>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_caramuru"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let messages = [
+        Request("initialize", json!({ "capabilities": {} })),
+        Notification("initialized", json!({})),
+    ]
+    .into_iter()
+    .chain(messages)
+    .chain([
+        Request("shutdown", Value::Null),
+        Notification("exit", Value::Null),
+    ]);
+
+    for (id, message) in messages.enumerate() {
+        let content = match message {
+            Request(method, params) => {
+                json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+            }
+            Notification(method, params) => {
+                json!({ "jsonrpc": "2.0", "method": method, "params": params })
+            }
+        }
+        .to_string();
+
+        write!(stdin, "Content-Length: {}\r\n\r\n{content}", content.len()).unwrap();
+    }
+
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+
+    let mut output = output.stdout.as_slice();
+    let mut responses = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    while !output.is_empty() {
+        let header_end = output.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let header = std::str::from_utf8(&output[..header_end]).unwrap();
+        let length = header["Content-Length: ".len()..].parse::<usize>().unwrap();
+        let message: Value =
+            serde_json::from_slice(&output[header_end + 4..header_end + 4 + length]).unwrap();
+
+        if message["method"] == "textDocument/publishDiagnostics" {
+            diagnostics.push(message["params"]["diagnostics"].clone());
+        } else if message["error"].is_null() {
+            responses.push(message["result"].clone());
+        } else {
+            responses.push(message["error"].clone());
+        }
+
+        output = &output[header_end + 4 + length..];
+    }
+
+    // The results of initialize and shutdown.
+    responses.remove(0);
+    responses.pop();
+
+    (responses, diagnostics)
+}
+
+fn range(start: (u64, u64), end: (u64, u64)) -> Value {
+    json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+#[test]
+fn reports_syntax_errors() {
+    let (_, diagnostics) = session(vec![
+        open("let x = 1;\nprint(x"),
+        open("let f = fn (a) => ;\nf(1)"),
+        open("print(\"99999999999\" + 99999999999)"),
+    ]);
+
+    assert_eq!(diagnostics[0][0]["range"], range((1, 7), (1, 7)));
+    assert_eq!(diagnostics[0][0]["severity"], 1);
+    assert_eq!(
+        diagnostics[0][0]["message"],
+        "expected token, but got eof\nexpected \")\""
+    );
+    assert_eq!(diagnostics[1][0]["code"], "zu::expected_function_body");
+    assert_eq!(diagnostics[2][0]["range"], range((0, 22), (0, 33)));
+    assert_eq!(
+        diagnostics[2][0]["message"],
+        "integer literal out of range\nintegers must fit in 32 bits"
+    );
+}
+
+#[test]
+fn warns_about_unbound_names() {
+    let (_, diagnostics) = session(vec![open(
+        "let f = fn () => { later + missing };\nlet later = 1;\nprint(f() + nowhere)",
+    )]);
+
+    let messages = diagnostics[0]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["message"].as_str().unwrap(),
+                diagnostic["severity"].clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Function bodies see the names bound where they are called, so `later`
+    // may be bound.
+    assert_eq!(
+        messages,
+        [
+            ("`missing` is not bound", json!(2)),
+            ("`nowhere` is not bound", json!(2)),
+        ]
+    );
+}
+
+#[test]
+fn navigates_bindings() {
+    let source = "\
+let fib = fn (n) => {
+  if (n < 2) { n } else { fib(n - 1) + fib(n - 2) }
+};
+let s = \"ção😀\"; print(s + fib(10))
+";
+
+    let (responses, diagnostics) = session(vec![
+        open(source),
+        at("textDocument/definition", 1, 27),
+        at("textDocument/definition", 1, 31),
+        at("textDocument/definition", 3, 23),
+        at("textDocument/hover", 3, 4),
+        at("textDocument/hover", 0, 5),
+        at("textDocument/hover", 1, 2),
+        Request(
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": URI } }),
+        ),
+    ]);
+
+    assert_eq!(diagnostics, [json!([])]);
+    assert_eq!(
+        responses[0],
+        json!({ "uri": URI, "range": range((0, 4), (0, 7)) })
+    );
+    assert_eq!(
+        responses[1],
+        json!({ "uri": URI, "range": range((0, 14), (0, 15)) })
+    );
+    assert_eq!(
+        responses[2],
+        json!({ "uri": URI, "range": range((3, 4), (3, 5)) })
+    );
+    assert_eq!(
+        responses[3]["contents"]["value"],
+        "```rinha\ns: string\n```\nlet bound on line 4"
+    );
+    assert_eq!(
+        responses[4]["contents"]["value"],
+        "```rinha\nfib: fn (n) => ?\n```\nlet bound on line 1"
+    );
+    assert_eq!(responses[5], Value::Null);
+
+    let symbols = responses[6]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| {
+            (
+                symbol["name"].as_str().unwrap(),
+                symbol["kind"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(symbols, [("fib", 12), ("s", 13)]);
+}
+
+#[test]
+fn formats_documents() {
+    let (responses, _) = session(vec![
+        open("let   x=1;print(x+2)"),
+        Request(
+            "textDocument/formatting",
+            json!({ "textDocument": { "uri": URI }, "options": { "tabSize": 2, "insertSpaces": true } }),
+        ),
+    ]);
+
+    assert_eq!(
+        responses[0],
+        json!([{ "range": range((0, 0), (0, 20)), "newText": "let x = 1;\nprint(x + 2)\n" }])
+    );
+}
@@ -0,0 +1,43 @@
+use caramuru::{
+    interpreter::{eval, output::Buffer, Options, Stats},
+    parse_source,
+};
+
+const PROGRAM: &str = "\
+let fib = fn (n) => {
+  if (n < 2) { n } else { fib(n - 1) + fib(n - 2) }
+};
+let show = fn (n) => { print(n) };
+let _ = show(fib(10));
+show(fib(10))
+";
+
+fn run(options: Options) -> (String, Stats) {
+    let file = parse_source("main.rinha", PROGRAM).unwrap();
+    let output = Buffer::default();
+    let stats = eval(file, &options, &mut output.clone()).unwrap();
+
+    (output.contents(), stats)
+}
+
+#[test]
+fn memoization_is_opt_in() {
+    let (output, stats) = run(Options::default());
+
+    assert_eq!(output, "55\n55\n");
+    assert_eq!((stats.memo.hits, stats.memo.misses), (0, 0));
+}
+
+#[test]
+fn caches_calls_to_pure_functions() {
+    let (output, stats) = run(Options {
+        memoize: true,
+        ..Options::default()
+    });
+
+    // `show` prints, so both of its calls run.
+    assert_eq!(output, "55\n55\n");
+    // fib(0) to fib(10) miss once. fib(n - 2) hits for n from 3 to 10, and
+    // so does the second fib(10).
+    assert_eq!((stats.memo.hits, stats.memo.misses), (8 + 1, 11));
+}
@@ -0,0 +1,109 @@
+use std::{cell::RefCell, env, fs, mem::size_of, process::Command, rc::Rc};
+
+use caramuru::{
+    interpreter::{eval, output::Buffer, Interpreter, Limit, LimitExceeded, Options, Value},
+    parse_source,
+};
+
+const TUPLE: usize = 2 * size_of::<Value>();
+
+fn peak(source: &str) -> usize {
+    let file = parse_source("test.rinha", source).unwrap();
+
+    eval(file, &Options::default(), &mut Buffer::default())
+        .unwrap()
+        .memory
+        .peak
+}
+
+#[test]
+fn copies_share_their_contents() {
+    let source = "let t = (1, 2);\nlet a = t;\nlet b = a;\nlet f = fn (x) => { (x, x) };\nf(b)";
+
+    assert_eq!(peak(source), 2 * TUPLE);
+}
+
+#[test]
+fn strings_count_their_capacity() {
+    let peak = peak("let s = \"rinha\";\nlet t = s;\n(s, t)");
+
+    assert_eq!(peak, "rinha".len() + TUPLE);
+}
+
+#[test]
+fn stops_before_allocating_over_the_limit() {
+    let source = format!("let s = \"{}\";\ns + s", "a".repeat(100));
+    let file = parse_source("test.rinha", &source).unwrap();
+    let mut interpreter = Interpreter::new(
+        Options {
+            max_memory: Some(250),
+            ..Options::default()
+        },
+        Buffer::default(),
+    );
+
+    let error = interpreter.eval(&file).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<LimitExceeded>().unwrap().limit,
+        Limit::Memory(250)
+    );
+    assert_eq!(interpreter.stats().memory.peak, 100);
+}
+
+#[test]
+fn command_line_reports_stats_when_evaluation_fails() {
+    let path = env::temp_dir().join(format!("caramuru-memory-{}.rinha", std::process::id()));
+
+    fs::write(&path, format!("let s = \"{}\";\ns + s", "a".repeat(100))).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_caramuru"))
+        .args(["--max-memory", "250", "--memory-stats", "--memo-stats"])
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    fs::remove_file(&path).unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("memory: 100 bytes peak"));
+    assert!(stderr.contains("memo: 0 hits, 0 misses"));
+    assert!(stderr.contains("exceeded"));
+}
+
+#[test]
+fn evaluations_keep_their_own_accounts() {
+    let file = parse_source("test.rinha", "(\"rinha\", 1)").unwrap();
+    let mut first = Interpreter::new(Options::default(), Buffer::default());
+    let mut second = Interpreter::new(Options::default(), Buffer::default());
+
+    let kept = first.eval(&file).unwrap();
+
+    second.eval(&file).unwrap();
+    drop(kept);
+
+    for interpreter in [first, second] {
+        assert_eq!(interpreter.stats().memory.live, "rinha".len() + TUPLE);
+    }
+}
+
+#[test]
+fn values_from_earlier_evaluations_are_not_counted() {
+    let mut interpreter = Interpreter::new(Options::default(), Buffer::default());
+    let earlier = parse_source("test.rinha", "(1, 2)").unwrap();
+    let kept = Rc::new(RefCell::new(Some(interpreter.eval(&earlier).unwrap())));
+
+    interpreter.register("release", move |_| {
+        kept.borrow_mut().take();
+
+        Ok(Value::Boolean(true))
+    });
+
+    let file = parse_source("test.rinha", "let t = (1, 2);\nlet r = release();\n(t, t)").unwrap();
+
+    interpreter.eval(&file).unwrap();
+
+    assert_eq!(interpreter.stats().memory.live, 2 * TUPLE);
+}
@@ -0,0 +1,110 @@
+use std::{cell::RefCell, env, fs, io, process::Command, rc::Rc};
+
+use caramuru::{
+    interpreter::{
+        eval,
+        output::{Buffer, Callback, Sink},
+        Interpreter, Options, Output,
+    },
+    parse_source,
+};
+
+const PROGRAM: &str = "let _ = print(\"a\");\nprint((1, 2))";
+
+#[test]
+fn buffer_collects_every_line() {
+    let output = Buffer::default();
+    let file = parse_source("test.rinha", PROGRAM).unwrap();
+
+    eval(file, &Options::default(), &mut output.clone()).unwrap();
+
+    assert_eq!(output.contents(), "a\n(1, 2)\n");
+}
+
+#[test]
+fn callback_receives_each_line() {
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let received = lines.clone();
+    let mut interpreter = Interpreter::new(
+        Options::default(),
+        Callback(move |line: &str| received.borrow_mut().push(line.to_string())),
+    );
+
+    interpreter
+        .eval(&parse_source("test.rinha", PROGRAM).unwrap())
+        .unwrap();
+
+    assert_eq!(*lines.borrow(), ["a", "(1, 2)"]);
+}
+
+#[test]
+fn sink_discards_the_output() {
+    let file = parse_source("test.rinha", PROGRAM).unwrap();
+
+    assert!(eval(file, &Options::default(), &mut Sink).is_ok());
+}
+
+struct Broken;
+
+impl Output for Broken {
+    fn print(&mut self, _line: &str) -> io::Result<()> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+#[test]
+fn failing_to_print_stops_evaluation() {
+    let file = parse_source("test.rinha", PROGRAM).unwrap();
+    let error = eval(file, &Options::default(), &mut Broken).unwrap_err();
+
+    assert_eq!(
+        error.downcast_ref::<io::Error>().unwrap().kind(),
+        io::ErrorKind::BrokenPipe
+    );
+}
+
+struct Unflushable;
+
+impl Output for Unflushable {
+    fn print(&mut self, _line: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+#[test]
+fn failing_to_flush_fails_evaluation() {
+    let file = parse_source("test.rinha", PROGRAM).unwrap();
+    let error = eval(file, &Options::default(), &mut Unflushable).unwrap_err();
+
+    assert!(error.is::<io::Error>());
+}
+
+#[test]
+fn evaluation_errors_win_over_flush_errors() {
+    let file = parse_source("test.rinha", "let _ = print(\"a\");\n1 / 0").unwrap();
+    let error = eval(file, &Options::default(), &mut Unflushable).unwrap_err();
+
+    assert!(error.to_string().contains("division by zero"));
+}
+
+#[test]
+fn stdout_is_flushed_when_evaluation_fails() {
+    let path = env::temp_dir().join(format!("caramuru-output-{}.rinha", std::process::id()));
+
+    fs::write(&path, "let _ = print(\"before\");\n1 / 0").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_caramuru"))
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    fs::remove_file(&path).unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "before\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("division by zero"));
+}
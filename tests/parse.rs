@@ -0,0 +1,80 @@
+use caramuru::{
+    ast::File,
+    parse::{parse, SyntaxError},
+};
+use miette::Diagnostic;
+
+#[test]
+fn reports_every_syntax_error() {
+    let error = parse(
+        "main.rinha",
+        "let f = fn (a) => ;\nlet g = fn (b) => { b + };\nprint(f(1)",
+    )
+    .unwrap_err();
+
+    assert_eq!(error.to_string(), "found 3 syntax errors in main.rinha");
+
+    let codes = error
+        .errors()
+        .iter()
+        .map(|error| error.code().unwrap().to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        codes,
+        [
+            "zu::expected_function_body",
+            "zu::unrecognized_token",
+            "zu::expected_token"
+        ]
+    );
+    assert_eq!(error.related().unwrap().count(), 3);
+}
+
+#[test]
+fn leaves_out_help_when_nothing_was_expected() {
+    let error = parse("main.rinha", "x ; x").unwrap_err();
+
+    assert!(matches!(
+        error.errors(),
+        [SyntaxError::UnrecognizedToken { span }] if span.offset() == 2
+    ));
+    assert!(error.errors()[0].help().is_none());
+}
+
+#[test]
+fn reports_integers_out_of_range() {
+    let error = parse(
+        "main.rinha",
+        "// 99999999999\nlet x99999999999 = \"99999999999\";\nprint(2147483648 + 2147483647)",
+    )
+    .unwrap_err();
+
+    let spans = error
+        .errors()
+        .iter()
+        .map(|error| match error {
+            SyntaxError::IntegerOutOfRange { span } => (span.offset(), span.len()),
+            error => panic!("unexpected {error}"),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(spans, [(55, 10)]);
+}
+
+#[test]
+fn converts_error_nodes_into_errors() {
+    let mut recovered = Vec::new();
+    let file = rinha::rinha::FileParser::new()
+        .parse(&mut recovered, "main.rinha", "let x = (1 +);\nprint(x)")
+        .unwrap();
+
+    assert_eq!(recovered.len(), 1);
+
+    let errors = File::try_from(file).unwrap_err();
+
+    assert!(matches!(
+        errors.as_slice(),
+        [SyntaxError::InvalidTerm { span, .. }] if span.offset() == 9
+    ));
+}
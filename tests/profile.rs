@@ -0,0 +1,127 @@
+use std::process::Command;
+
+use caramuru::{
+    interpreter::{eval_with_hook, output::Buffer, Options},
+    parse_source,
+    profile::{Profile, Profiler},
+    source::{Position, Source, SourceMap},
+};
+
+const PROGRAM: &str = "\
+let fib = fn (n) => {
+  if (n < 2) { n } else { fib(n - 1) + fib(n - 2) }
+};
+let sum = fn (n) => {
+  if (n == 0) { 0 } else { fib(3) + sum(n - 1) }
+};
+print(sum(4) + fib(6))
+";
+
+fn profile(options: Options) -> Profile {
+    let file = parse_source("main.rinha", PROGRAM).unwrap();
+    let output = Buffer::default();
+    let mut profiler = Profiler::new();
+
+    eval_with_hook(file, &options, &mut output.clone(), &mut profiler).unwrap();
+
+    assert_eq!(output.contents(), "16\n");
+
+    profiler.finish()
+}
+
+#[test]
+fn counts_calls_and_depth() {
+    let profile = profile(Options::default());
+
+    let mut functions = profile
+        .functions
+        .iter()
+        .map(|function| (function.name.as_str(), function.calls, function.max_depth))
+        .collect::<Vec<_>>();
+
+    functions.sort();
+
+    // fib(3) makes 5 calls and fib(6) 25.
+    assert_eq!(functions, [("fib", 4 * 5 + 25, 6), ("sum", 5, 5)]);
+
+    for function in &profile.functions {
+        assert!(function.exclusive <= function.inclusive);
+        assert!(function.inclusive <= profile.total);
+    }
+
+    let sum = profile
+        .functions
+        .iter()
+        .find(|function| function.name == "sum")
+        .unwrap();
+
+    let start = sum.location.as_ref().unwrap().start;
+
+    assert_eq!(
+        Source::new(PROGRAM.to_string()).position(start),
+        Position {
+            line: 4,
+            column: 11
+        }
+    );
+}
+
+#[test]
+fn profiles_every_call_when_memoizing() {
+    let profile = profile(Options {
+        memoize: true,
+        ..Options::default()
+    });
+
+    let fib = profile
+        .functions
+        .iter()
+        .find(|function| function.name == "fib")
+        .unwrap();
+
+    assert_eq!(fib.calls, 4 * 5 + 25);
+}
+
+#[test]
+fn command_line_profiles_every_call_when_memoizing() {
+    let output = Command::new(env!("CARGO_BIN_EXE_caramuru"))
+        .args(["--memo", "--profile", "tests/conformance/fib.rinha"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let table = String::from_utf8(output.stderr).unwrap();
+    let fib = table.lines().find(|line| line.starts_with("fib ")).unwrap();
+
+    assert_eq!(fib.split_whitespace().nth(2), Some("177"));
+}
+
+#[test]
+fn reports_tables_and_folded_stacks() {
+    let profile = profile(Options::default());
+    let mut sources = SourceMap::new();
+
+    sources.add("main.rinha", PROGRAM.to_string());
+
+    let table = profile.table(&sources);
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert!(lines[0].starts_with("function  location"));
+    assert!(lines.iter().any(|line| line.starts_with("sum ")
+        && line.contains("main.rinha:4:11")
+        && line.contains(" 5 ")));
+    assert!(lines.last().unwrap().starts_with("total "));
+
+    let folded = profile.folded(&sources);
+
+    for line in folded.lines() {
+        let (stack, time) = line.rsplit_once(' ').unwrap();
+
+        assert!(stack.starts_with("<main>"));
+        assert!(time.parse::<u128>().unwrap() > 0);
+
+        // fib never calls sum.
+        assert!(!stack.contains("fib (main.rinha:1:11);sum"));
+    }
+}
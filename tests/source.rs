@@ -0,0 +1,110 @@
+use caramuru::{
+    ast::Location,
+    interpreter::{describe_error, eval, output::Buffer, Options},
+    parse_source,
+    source::{Position, SourceMap},
+};
+
+fn location(filename: &str, start: usize, end: usize) -> Location {
+    Location {
+        start,
+        end,
+        filename: filename.to_string(),
+    }
+}
+
+#[test]
+fn maps_offsets_in_every_file() {
+    let mut sources = SourceMap::new();
+
+    sources.add("a.rinha", "let x = 1;\nprint(x)".to_string());
+    sources.add("b.rinha", "print(\"olá\")\r\nprint(2)\r\n".to_string());
+
+    assert_eq!(
+        sources.position(&location("a.rinha", 17, 18)),
+        Some(Position { line: 2, column: 7 })
+    );
+    assert_eq!(
+        sources.span(&location("b.rinha", 6, 12)),
+        Some((
+            Position { line: 1, column: 7 },
+            Position {
+                line: 1,
+                column: 12
+            }
+        ))
+    );
+    assert_eq!(sources.position(&location("c.rinha", 0, 1)), None);
+
+    assert_eq!(
+        sources.offset("b.rinha", Position { line: 2, column: 7 }),
+        Some(21)
+    );
+    assert_eq!(
+        sources.offset(
+            "b.rinha",
+            Position {
+                line: 1,
+                column: 14
+            }
+        ),
+        None
+    );
+
+    assert_eq!(
+        sources.snippet(&location("b.rinha", 6, 12)),
+        Some("\"olá\"")
+    );
+    assert_eq!(sources.snippet(&location("b.rinha", 9, 10)), None);
+
+    assert_eq!(
+        sources.describe(&location("b.rinha", 15, 20)),
+        "b.rinha:2:1"
+    );
+    assert_eq!(sources.describe(&location("c.rinha", 3, 5)), "c.rinha:3..5");
+}
+
+#[test]
+fn handles_crlf_line_breaks() {
+    let mut sources = SourceMap::new();
+    let source = sources.add("main.rinha", "let a = 1;\r\n\r\nprint(a)".to_string());
+
+    assert_eq!(source.line_count(), 3);
+    assert_eq!(source.line(1), Some("let a = 1;"));
+    assert_eq!(source.line(2), Some(""));
+    assert_eq!(source.line(3), Some("print(a)"));
+
+    // Line breaks are past the end of their line.
+    assert_eq!(
+        source.position(11),
+        Position {
+            line: 1,
+            column: 11
+        }
+    );
+    assert_eq!(source.position(14), Position { line: 3, column: 1 });
+}
+
+#[test]
+fn describes_errors_by_line_and_column() {
+    let program = "let loop = fn (n) => {\n  loop(n + 1)\n};\nloop(0)";
+    let file = parse_source("main.rinha", program).unwrap();
+    let mut sources = SourceMap::new();
+
+    sources.add("main.rinha", program.to_string());
+
+    let error = eval(
+        file,
+        &Options {
+            max_depth: Some(10),
+            ..Options::default()
+        },
+        &mut Buffer::default(),
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        describe_error(error.as_ref(), &sources),
+        "exceeded the maximum call depth of 10 at main.rinha:2:3"
+    );
+}
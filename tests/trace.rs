@@ -0,0 +1,78 @@
+use caramuru::{
+    interpreter::{eval_with_hook, output::Buffer, Options},
+    parse_source,
+    source::SourceMap,
+    trace::{TraceOptions, Tracer},
+};
+
+const PROGRAM: &str = "\
+let double = fn (x) => { x * 2 };
+let quad = fn (x) => { double(double(x)) };
+print(quad(1))
+";
+
+fn trace(options: TraceOptions) -> String {
+    let file = parse_source("main.rinha", PROGRAM).unwrap();
+    let mut sources = SourceMap::new();
+    let mut trace = Vec::new();
+
+    sources.add("main.rinha", PROGRAM.to_string());
+
+    let mut tracer = Tracer::new(&mut trace, sources, options);
+
+    eval_with_hook(
+        file,
+        &Options::default(),
+        &mut Buffer::default(),
+        &mut tracer,
+    )
+    .unwrap();
+
+    drop(tracer);
+
+    String::from_utf8(trace).unwrap()
+}
+
+#[test]
+fn traces_terms_calls_and_bindings() {
+    let trace = trace(TraceOptions::default());
+
+    assert!(trace.starts_with(
+        "let let double = fn (x) => { x * 2 };…\n\
+         function fn (x) => { x * 2 }\n\
+         bind double = #function\n"
+    ));
+    assert!(trace.contains(
+        "→ quad(x = 1)\n\
+         \x20 call double(double(x))\n"
+    ));
+    assert!(trace.contains(
+        "  → double(x = 2)\n\
+         \x20   binary x * 2\n\
+         \x20   var x\n\
+         \x20   int 2\n\
+         \x20 ← double = 4\n\
+         ← quad = 4\n"
+    ));
+}
+
+#[test]
+fn filters_by_function_and_depth() {
+    let trace = trace(TraceOptions {
+        function: Some("quad".to_string()),
+        max_depth: Some(1),
+    });
+
+    assert_eq!(
+        trace,
+        "→ quad(x = 1)\n\
+         \x20 call double(double(x))\n\
+         \x20 call double(x)\n\
+         \x20 var x\n\
+         \x20 → double(x = 1)\n\
+         \x20 ← double = 2\n\
+         \x20 → double(x = 2)\n\
+         \x20 ← double = 4\n\
+         ← quad = 4\n"
+    );
+}
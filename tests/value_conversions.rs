@@ -0,0 +1,91 @@
+use caramuru::{
+    interpreter::{output::Buffer, Interpreter, Options, Value},
+    parse_source,
+};
+
+fn eval(source: &str) -> Value {
+    let file = parse_source("test.rinha", source).unwrap();
+
+    Interpreter::new(Options::default(), Buffer::default())
+        .eval(&file)
+        .unwrap()
+}
+
+#[test]
+fn rust_values_round_trip() {
+    assert_eq!(i32::try_from(Value::from(42)).unwrap(), 42);
+    assert!(bool::try_from(Value::from(true)).unwrap());
+    assert_eq!(String::try_from(Value::from("rinha")).unwrap(), "rinha");
+
+    let tuple: (i32, (String, bool)) = Value::from((1, ("a", false))).try_into().unwrap();
+
+    assert_eq!(tuple, (1, ("a".to_string(), false)));
+}
+
+#[test]
+fn mismatched_conversion_fails() {
+    let error = i32::try_from(Value::from("1")).unwrap_err();
+
+    assert_eq!(error.to_string(), "expected integer, got string");
+    assert!(<(i32, i32)>::try_from(Value::from(1)).is_err());
+}
+
+#[test]
+fn json_round_trips() {
+    for json in ["42", "-7", "\"rinha\"", "true", "[1,[\"a\",[false,2]]]"] {
+        let value: Value = serde_json::from_str(json).unwrap();
+
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+    }
+}
+
+#[test]
+fn invalid_json_is_rejected() {
+    for json in [
+        "null",
+        "1.5",
+        "4294967296",
+        "[]",
+        "[1]",
+        "[1,2,3]",
+        "{\"a\":1}",
+    ] {
+        assert!(serde_json::from_str::<Value>(json).is_err(), "{json}");
+    }
+}
+
+#[test]
+fn evaluated_values_serialize() {
+    let value = eval("let x = (1, \"a\" + 2); (x, true)");
+
+    assert_eq!(serde_json::to_string(&value).unwrap(), "[[1,\"a2\"],true]");
+}
+
+#[test]
+fn functions_are_not_serializable() {
+    let value = eval("fn (x) => x");
+
+    assert!(serde_json::to_string(&value).is_err());
+    assert!(serde_json::to_string(&Value::from((1, value))).is_err());
+}
+
+#[test]
+fn json_input_is_visible_to_programs() {
+    let output = Buffer::default();
+    let mut interpreter = Interpreter::new(Options::default(), output.clone());
+
+    interpreter.define("input", serde_json::from_str("[20, [1, 1]]").unwrap());
+
+    let file = parse_source(
+        "test.rinha",
+        "let pair = second(input); first(input) + first(pair) + second(pair)",
+    )
+    .unwrap();
+
+    let result = interpreter.eval(&file).unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&result).unwrap(),
+        serde_json::json!(22)
+    );
+}
@@ -0,0 +1,83 @@
+use caramuru::{
+    ast::{
+        self,
+        fold::{walk_binary, Fold},
+        visit::{walk_function, Visitor},
+    },
+    fmt::{format_file, DEFAULT_WIDTH},
+    parse_source,
+};
+
+#[derive(Default)]
+struct Parameters<'a> {
+    names: Vec<&'a str>,
+    reads: usize,
+}
+
+impl<'a> Visitor<'a> for Parameters<'a> {
+    fn visit_function(&mut self, t: &'a ast::Function) {
+        self.names
+            .extend(t.parameters.iter().map(|parameter| parameter.text.as_str()));
+
+        walk_function(self, t);
+    }
+
+    fn visit_var(&mut self, _t: &'a ast::Variable) {
+        self.reads += 1;
+    }
+}
+
+#[test]
+fn visitor_reaches_every_node() {
+    let file = parse_source(
+        "test.rinha",
+        "let f = fn (a, b) => (a, fn (c) => b + c); print(first(f(1, 2)))",
+    )
+    .unwrap();
+    let mut visitor = Parameters::default();
+
+    visitor.visit_term(&file.expression);
+
+    assert_eq!(visitor.names, ["a", "b", "c"]);
+    assert_eq!(visitor.reads, 4);
+}
+
+struct SwapOperands;
+
+impl Fold for SwapOperands {
+    fn fold_binary(&mut self, t: ast::Binary) -> ast::Term {
+        match walk_binary(self, t) {
+            ast::Term::Binary(t) => ast::Term::Binary(ast::Binary {
+                lhs: t.rhs,
+                rhs: t.lhs,
+                ..t
+            }),
+            term => term,
+        }
+    }
+
+    fn fold_int(&mut self, t: ast::Integer) -> ast::Term {
+        ast::Term::Str(ast::Str {
+            value: t.value.to_string(),
+            location: t.location,
+        })
+    }
+}
+
+#[test]
+fn fold_rebuilds_the_tree() {
+    let file = parse_source("test.rinha", "let x = 1 - (2 - 3); print(x)").unwrap();
+    let expected = parse_source("test.rinha", "let x = (\"3\" - \"2\") - \"1\"; print(x)").unwrap();
+
+    let folded = SwapOperands.fold_term(file.expression);
+
+    let folded = ast::File {
+        expression: folded,
+        ..file
+    };
+
+    assert_eq!(
+        format_file(&folded, DEFAULT_WIDTH),
+        format_file(&expected, DEFAULT_WIDTH)
+    );
+}